@@ -0,0 +1,59 @@
+use unitn_market_2022::good::good_kind::GoodKind;
+
+use crate::{
+    AllocationProfile, GoodsTable, IdleDecay, MeanBalancing, MeanMode, PricingStrategy, QuoteMode,
+    RebalanceSchedule, RebalanceStrategy, TieredPricing, DEFAULT_ACTIVITY_WINDOW_DAYS,
+    DEFAULT_MAX_RETIRED_TOKENS, DEFAULT_TOKEN_RETENTION_DAYS,
+};
+
+/// Per instance settings of a `BVCMarket`.
+/// `BVCConfig::default()` gives the behaviour documented in the crate docs, override only the fields you need.
+pub struct BVCConfig {
+    /// Days a redeemed, expired or cancelled token is remembered before being pruned
+    pub token_retention_days: u64,
+    /// Maximum number of redeemed, expired or cancelled tokens remembered at the same time
+    pub max_retired_tokens: usize,
+    /// Pricing policy, `TieredPricing` by default
+    pub pricing: Box<dyn PricingStrategy>,
+    /// How buy prices of whole orders are computed, `QuoteMode::Flat` by default
+    pub quote_mode: QuoteMode,
+    /// Days of trades counted in `PricingContext::recent_activity`
+    pub activity_window_days: u64,
+    /// Decay of the prices of idle goods, disabled by default
+    pub idle_decay: Option<IdleDecay>,
+    /// Which mean the goods are priced against, `MeanMode::Initial` by default
+    pub mean_mode: MeanMode,
+    /// How the goods are rebalanced, `MeanBalancing` by default
+    pub rebalance: Box<dyn RebalanceStrategy>,
+    /// When the goods are rebalanced, `RebalanceSchedule::Random` with the documented probability by default
+    pub rebalance_schedule: RebalanceSchedule,
+    /// Share of the eur value lost in every rebalance conversion, none by default
+    pub rebalance_fee: f32,
+    /// Goods traded by the market, with their default exchange rates and initial allocation rules
+    pub goods: GoodsTable,
+    /// Good in which the market settles its trades and keeps its reserve, eur by default.
+    /// Prices, budget, bids and offers are still expressed in eur, as the `Market` trait requires.
+    pub base_good: GoodKind,
+    /// How `random_with_config` allocates the starting capital, `AllocationProfile::Standard` by default
+    pub allocation: AllocationProfile,
+}
+
+impl Default for BVCConfig {
+    fn default() -> Self {
+        BVCConfig {
+            token_retention_days: DEFAULT_TOKEN_RETENTION_DAYS,
+            max_retired_tokens: DEFAULT_MAX_RETIRED_TOKENS,
+            pricing: Box::new(TieredPricing::default()),
+            quote_mode: QuoteMode::Flat,
+            activity_window_days: DEFAULT_ACTIVITY_WINDOW_DAYS,
+            idle_decay: None,
+            mean_mode: MeanMode::Initial,
+            rebalance: Box::new(MeanBalancing),
+            rebalance_schedule: RebalanceSchedule::default(),
+            rebalance_fee: 0.0,
+            goods: GoodsTable::default(),
+            base_good: GoodKind::EUR,
+            allocation: AllocationProfile::Standard,
+        }
+    }
+}
@@ -0,0 +1,461 @@
+use std::collections::HashMap;
+
+use rand::{seq::SliceRandom, thread_rng, Rng};
+use unitn_market_2022::good::good_kind::GoodKind;
+
+use crate::{
+    DEFAULT_USD_EUR_EXCHANGE_RATE, DEFAULT_YEN_EUR_EXCHANGE_RATE, DEFAULT_YUAN_EUR_EXCHANGE_RATE,
+    EUR_HEAVY_LOWER_BOUND_INIT_PERCENTAGE, EUR_HEAVY_UPPER_BOUND_INIT_PERCENTAGE,
+    EUR_LOWER_BOUND_INIT_PERCENTAGE, EUR_UPPER_BOUND_INIT_PERCENTAGE,
+    SECOND_GOOD_LOWER_BOUND_INIT_PERCENTAGE, SECOND_GOOD_UPPER_BOUND_INIT_PERCENTAGE,
+    SPECIALIST_EUR_LOWER_BOUND_INIT_PERCENTAGE, SPECIALIST_EUR_UPPER_BOUND_INIT_PERCENTAGE,
+    SPECIALIST_GOOD_LOWER_BOUND_INIT_PERCENTAGE, SPECIALIST_GOOD_UPPER_BOUND_INIT_PERCENTAGE,
+    THIRD_GOOD_LOWER_BOUND_INIT_PERCENTAGE, THIRD_GOOD_UPPER_BOUND_INIT_PERCENTAGE,
+};
+
+/// A good traded by the market
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GoodSpec {
+    pub kind: GoodKind,
+    /// Becomes `PricingContext::default_price`, it must be positive and finite, and `1.0` for eur
+    pub default_exchange_rate: f32,
+}
+
+/// A step of the initial allocation: a random share in `[lower_bound, upper_bound)` of the capital still to allocate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AllocationStep {
+    pub lower_bound: f32,
+    pub upper_bound: f32,
+}
+
+/// Goods traded by the market, and how `random` allocates the starting capital among them.
+/// The base good of the market (`BVCConfig::base_good`) must always be part of the table, and no good
+/// can appear twice, otherwise building the market panics.
+#[derive(Debug, Clone)]
+pub struct GoodsTable {
+    /// Every good traded by the market, the base one included
+    pub goods: Vec<GoodSpec>,
+    /// Share of the starting capital allocated to the base good
+    pub base_allocation: AllocationStep,
+    /// Shares of the capital left allocated to the other goods, taken in random order.
+    /// The last good gets what is left, and the goods without a step share it equally.
+    pub allocation_steps: Vec<AllocationStep>,
+}
+
+/// How `random` allocates the starting capital among the goods of the `GoodsTable`.
+/// Whatever the profile, the allocated eur values sum up to the starting capital.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AllocationProfile {
+    /// The allocation steps of the `GoodsTable`, as documented
+    #[default]
+    Standard,
+    /// Every good gets the same share of the capital
+    Balanced,
+    /// The base good gets `50%`-`60%` of the capital, the other goods share what is left equally
+    EurHeavy,
+    /// A random good other than the base one gets `60%`-`70%` of the capital, the base good
+    /// `50%`-`60%` of what is left, and the other goods share the rest equally
+    SingleCurrencySpecialist,
+    /// Shares drawn from a flat Dirichlet distribution, every split of the capital being equally likely
+    Dirichlet,
+}
+
+impl Default for GoodsTable {
+    /// The four goods of the tournament, allocated as documented
+    fn default() -> Self {
+        GoodsTable {
+            goods: vec![
+                GoodSpec {
+                    kind: GoodKind::EUR,
+                    default_exchange_rate: 1.0,
+                },
+                GoodSpec {
+                    kind: GoodKind::USD,
+                    default_exchange_rate: DEFAULT_USD_EUR_EXCHANGE_RATE,
+                },
+                GoodSpec {
+                    kind: GoodKind::YEN,
+                    default_exchange_rate: DEFAULT_YEN_EUR_EXCHANGE_RATE,
+                },
+                GoodSpec {
+                    kind: GoodKind::YUAN,
+                    default_exchange_rate: DEFAULT_YUAN_EUR_EXCHANGE_RATE,
+                },
+            ],
+            base_allocation: AllocationStep {
+                lower_bound: EUR_LOWER_BOUND_INIT_PERCENTAGE,
+                upper_bound: EUR_UPPER_BOUND_INIT_PERCENTAGE,
+            },
+            allocation_steps: vec![
+                AllocationStep {
+                    lower_bound: SECOND_GOOD_LOWER_BOUND_INIT_PERCENTAGE,
+                    upper_bound: SECOND_GOOD_UPPER_BOUND_INIT_PERCENTAGE,
+                },
+                AllocationStep {
+                    lower_bound: THIRD_GOOD_LOWER_BOUND_INIT_PERCENTAGE,
+                    upper_bound: THIRD_GOOD_UPPER_BOUND_INIT_PERCENTAGE,
+                },
+            ],
+        }
+    }
+}
+
+impl AllocationStep {
+    fn draw<R: Rng>(&self, rng: &mut R, capital: f32) -> f32 {
+        // * Nothing is left to allocate, and the range to draw from would be empty
+        if capital <= 0.0 {
+            return 0.0;
+        }
+        if self.upper_bound > self.lower_bound {
+            rng.gen_range(capital * self.lower_bound, capital * self.upper_bound)
+        } else {
+            capital * self.lower_bound
+        }
+    }
+}
+
+impl GoodsTable {
+    /// `GoodSpec::default_exchange_rate` of the good, `None` if the market does not trade it
+    pub fn default_exchange_rate(&self, kind: GoodKind) -> Option<f32> {
+        self.goods
+            .iter()
+            .find(|good| good.kind == kind)
+            .map(|good| good.default_exchange_rate)
+    }
+
+    // * Panics if the table can not be used by a market settling its trades in `base`
+    pub(crate) fn validate(&self, base: GoodKind) {
+        if !self.goods.iter().any(|good| good.kind == base) {
+            panic!("The base good {} is missing from the goods table !", base);
+        }
+        for (index, good) in self.goods.iter().enumerate() {
+            if self.goods[..index]
+                .iter()
+                .any(|other| other.kind == good.kind)
+            {
+                panic!("The good {} appears twice in the goods table !", good.kind);
+            }
+            if !good.default_exchange_rate.is_finite() || good.default_exchange_rate <= 0.0 {
+                panic!(
+                    "The default exchange rate of {} must be positive and finite, found {} !",
+                    good.kind, good.default_exchange_rate
+                );
+            }
+            if good.kind == GoodKind::EUR && good.default_exchange_rate != 1.0 {
+                panic!(
+                    "The default exchange rate of EUR must be 1.0, found {} !",
+                    good.default_exchange_rate
+                );
+            }
+        }
+    }
+
+    // * Eur value of the capital allocated to every good according to `profile`
+    pub(crate) fn random_allocation(
+        &self,
+        profile: AllocationProfile,
+        capital: f32,
+        base: GoodKind,
+    ) -> HashMap<GoodKind, f32> {
+        match profile {
+            AllocationProfile::Standard => self.standard_allocation(capital, base),
+            AllocationProfile::Balanced => self
+                .goods
+                .iter()
+                .map(|good| (good.kind, capital / self.goods.len() as f32))
+                .collect(),
+            AllocationProfile::EurHeavy => {
+                let step = AllocationStep {
+                    lower_bound: EUR_HEAVY_LOWER_BOUND_INIT_PERCENTAGE,
+                    upper_bound: EUR_HEAVY_UPPER_BOUND_INIT_PERCENTAGE,
+                };
+                let mut allocation = HashMap::new();
+                let left = self.allocate_base(&mut allocation, step, capital, base);
+                self.share_equally(&mut allocation, left);
+                allocation
+            }
+            AllocationProfile::SingleCurrencySpecialist => {
+                self.specialist_allocation(capital, base)
+            }
+            AllocationProfile::Dirichlet => {
+                let mut rng = thread_rng();
+                // * Normalized exponential draws follow a flat Dirichlet distribution
+                let weights: Vec<(GoodKind, f32)> = self
+                    .goods
+                    .iter()
+                    .map(|good| (good.kind, -rng.gen_range(f32::MIN_POSITIVE, 1.0).ln()))
+                    .collect();
+                let total_weight: f32 = weights.iter().map(|(_, weight)| weight).sum();
+                weights
+                    .into_iter()
+                    .map(|(kind, weight)| (kind, capital * weight / total_weight))
+                    .collect()
+            }
+        }
+    }
+
+    // * The allocation steps of the table, the goods after the base one are shuffled
+    fn standard_allocation(&self, capital: f32, base: GoodKind) -> HashMap<GoodKind, f32> {
+        let mut rng = thread_rng();
+        let mut allocation = HashMap::new();
+        let mut left = capital;
+
+        let mut others: Vec<GoodKind> = Vec::new();
+        for good in &self.goods {
+            if good.kind == base {
+                let base_value = self.base_allocation.draw(&mut rng, left);
+                allocation.insert(base, base_value);
+                left -= base_value;
+            } else {
+                others.push(good.kind);
+            }
+        }
+
+        others.shuffle(&mut rng);
+        for (index, kind) in others.iter().enumerate() {
+            let value = match self.allocation_steps.get(index) {
+                Some(step) if index + 1 < others.len() => step.draw(&mut rng, left),
+                _ => left / (others.len() - index) as f32,
+            };
+            allocation.insert(*kind, value);
+            left -= value;
+        }
+
+        allocation
+    }
+
+    fn specialist_allocation(&self, capital: f32, base: GoodKind) -> HashMap<GoodKind, f32> {
+        let mut rng = thread_rng();
+        let mut allocation = HashMap::new();
+        let mut left = capital;
+
+        let others: Vec<GoodKind> = self
+            .goods
+            .iter()
+            .map(|good| good.kind)
+            .filter(|kind| *kind != base)
+            .collect();
+        if let Some(specialty) = others.choose(&mut rng) {
+            let step = AllocationStep {
+                lower_bound: SPECIALIST_GOOD_LOWER_BOUND_INIT_PERCENTAGE,
+                upper_bound: SPECIALIST_GOOD_UPPER_BOUND_INIT_PERCENTAGE,
+            };
+            let value = step.draw(&mut rng, left);
+            allocation.insert(*specialty, value);
+            left -= value;
+        }
+
+        let step = AllocationStep {
+            lower_bound: SPECIALIST_EUR_LOWER_BOUND_INIT_PERCENTAGE,
+            upper_bound: SPECIALIST_EUR_UPPER_BOUND_INIT_PERCENTAGE,
+        };
+        let left = self.allocate_base(&mut allocation, step, left, base);
+        self.share_equally(&mut allocation, left);
+        allocation
+    }
+
+    // * Allocates the base good its step of `left`, returning what is left.
+    // * If the base good is the only one still to allocate, it gets all of `left`.
+    fn allocate_base(
+        &self,
+        allocation: &mut HashMap<GoodKind, f32>,
+        step: AllocationStep,
+        left: f32,
+        base: GoodKind,
+    ) -> f32 {
+        let others_left = self
+            .goods
+            .iter()
+            .any(|good| good.kind != base && !allocation.contains_key(&good.kind));
+        let value = if others_left {
+            step.draw(&mut thread_rng(), left)
+        } else {
+            left
+        };
+        allocation.insert(base, value);
+        left - value
+    }
+
+    // * Shares `left` equally among the goods not allocated yet
+    fn share_equally(&self, allocation: &mut HashMap<GoodKind, f32>, left: f32) {
+        let unallocated: Vec<GoodKind> = self
+            .goods
+            .iter()
+            .map(|good| good.kind)
+            .filter(|kind| !allocation.contains_key(kind))
+            .collect();
+        for kind in &unallocated {
+            allocation.insert(*kind, left / unallocated.len() as f32);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use unitn_market_2022::good::consts::STARTING_CAPITAL;
+
+    use super::*;
+
+    const DRAWS: usize = 1000;
+
+    fn allocations(profile: AllocationProfile) -> Vec<HashMap<GoodKind, f32>> {
+        let table = GoodsTable::default();
+        (0..DRAWS)
+            .map(|_| table.random_allocation(profile, STARTING_CAPITAL, GoodKind::EUR))
+            .collect()
+    }
+
+    fn share(allocation: &HashMap<GoodKind, f32>, kind: GoodKind) -> f32 {
+        allocation[&kind] / STARTING_CAPITAL
+    }
+
+    fn others(allocation: &HashMap<GoodKind, f32>) -> Vec<f32> {
+        allocation
+            .iter()
+            .filter(|(kind, _)| **kind != GoodKind::EUR)
+            .map(|(_, value)| *value)
+            .collect()
+    }
+
+    // * Bounds of the allocation steps, up to the rounding of the f32 draws
+    fn assert_within(share: f32, lower_bound: f32, upper_bound: f32) {
+        assert!(
+            share >= lower_bound * (1.0 - 1e-6) && share <= upper_bound * (1.0 + 1e-6),
+            "{} is not in [{}, {})",
+            share,
+            lower_bound,
+            upper_bound
+        );
+    }
+
+    fn assert_close(left: f32, right: f32) {
+        assert!(
+            (left - right).abs() <= right.abs() * 1e-4,
+            "{} is not close to {}",
+            left,
+            right
+        );
+    }
+
+    #[test]
+    fn every_profile_allocates_the_starting_capital() {
+        let profiles = [
+            AllocationProfile::Standard,
+            AllocationProfile::Balanced,
+            AllocationProfile::EurHeavy,
+            AllocationProfile::SingleCurrencySpecialist,
+            AllocationProfile::Dirichlet,
+        ];
+        for profile in profiles {
+            for allocation in allocations(profile) {
+                assert_eq!(allocation.len(), 4, "{:?}", profile);
+                assert!(
+                    allocation.values().all(|value| *value > 0.0),
+                    "{:?}",
+                    profile
+                );
+                assert_close(allocation.values().sum(), STARTING_CAPITAL);
+            }
+        }
+    }
+
+    #[test]
+    fn standard_allocation_follows_the_documented_steps() {
+        for allocation in allocations(AllocationProfile::Standard) {
+            assert_within(
+                share(&allocation, GoodKind::EUR),
+                EUR_LOWER_BOUND_INIT_PERCENTAGE,
+                EUR_UPPER_BOUND_INIT_PERCENTAGE,
+            );
+        }
+    }
+
+    #[test]
+    fn balanced_allocation_is_equal() {
+        for allocation in allocations(AllocationProfile::Balanced) {
+            for value in allocation.values() {
+                assert_close(*value, STARTING_CAPITAL / 4.0);
+            }
+        }
+    }
+
+    #[test]
+    fn eur_heavy_allocation_stays_in_its_bounds() {
+        for allocation in allocations(AllocationProfile::EurHeavy) {
+            assert_within(
+                share(&allocation, GoodKind::EUR),
+                EUR_HEAVY_LOWER_BOUND_INIT_PERCENTAGE,
+                EUR_HEAVY_UPPER_BOUND_INIT_PERCENTAGE,
+            );
+            let others = others(&allocation);
+            for value in &others {
+                assert_close(*value, others[0]);
+            }
+        }
+    }
+
+    #[test]
+    fn specialist_allocation_stays_in_its_bounds() {
+        for allocation in allocations(AllocationProfile::SingleCurrencySpecialist) {
+            // * The specialty is by far the biggest good
+            let mut others = others(&allocation);
+            others.sort_by(|a, b| b.partial_cmp(a).unwrap());
+            assert_within(
+                others[0] / STARTING_CAPITAL,
+                SPECIALIST_GOOD_LOWER_BOUND_INIT_PERCENTAGE,
+                SPECIALIST_GOOD_UPPER_BOUND_INIT_PERCENTAGE,
+            );
+
+            let left = STARTING_CAPITAL - others[0];
+            assert_within(
+                allocation[&GoodKind::EUR] / left,
+                SPECIALIST_EUR_LOWER_BOUND_INIT_PERCENTAGE,
+                SPECIALIST_EUR_UPPER_BOUND_INIT_PERCENTAGE,
+            );
+            assert_close(others[1], others[2]);
+        }
+    }
+
+    fn table_with(goods: Vec<GoodSpec>) -> GoodsTable {
+        GoodsTable {
+            goods,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "appears twice in the goods table")]
+    fn duplicate_goods_are_rejected() {
+        let mut goods = GoodsTable::default().goods;
+        goods.push(GoodSpec {
+            kind: GoodKind::USD,
+            default_exchange_rate: 1.0,
+        });
+        table_with(goods).validate(GoodKind::EUR);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be positive and finite")]
+    fn nan_exchange_rates_are_rejected() {
+        let mut goods = GoodsTable::default().goods;
+        goods[1].default_exchange_rate = f32::NAN;
+        table_with(goods).validate(GoodKind::EUR);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be positive and finite")]
+    fn infinite_exchange_rates_are_rejected() {
+        let mut goods = GoodsTable::default().goods;
+        goods[1].default_exchange_rate = f32::INFINITY;
+        table_with(goods).validate(GoodKind::EUR);
+    }
+
+    #[test]
+    fn steps_draw_nothing_from_an_empty_capital() {
+        let step = AllocationStep {
+            lower_bound: 0.5,
+            upper_bound: 0.6,
+        };
+        assert_eq!(step.draw(&mut thread_rng(), 0.0), 0.0);
+    }
+}
@@ -25,6 +25,16 @@
 //!
 //!> Note: In the initialization, good order is randomized.
 //!
+//!The goods, their default exchange rates and these allocation steps come from a `GoodsTable`, `GoodsTable::default()` being the one described above. A market built with `BVCMarket::with_goods` (or with a custom `BVCConfig::goods`) can trade any set of goods including `eur`, with other default exchange rates: the `mean` is then taken over all its goods except `eur`, and the goods after the last allocation step share what is left of the capital equally.
+//!
+//!Other allocation profiles can be chosen with `BVCMarket::random_with_profile` (or `BVCConfig::allocation`), the one above being `AllocationProfile::Standard`. Whatever the profile, the eur value of the goods always sums up to the starting capital:
+//!- `Balanced`: every good gets the same share of the capital.
+//!- `EurHeavy`: `eur` gets $x\in[50\%,60\%)$ of the capital, the other goods share what is left equally.
+//!- `SingleCurrencySpecialist`: a random good other than `eur` gets $x\in[60\%,70\%)$ of the capital, `eur` gets $y\in[50\%,60\%)$ of what is left, and the other goods share the rest equally.
+//!- `Dirichlet`: the shares are drawn from a flat Dirichlet distribution, so that every split of the capital is equally likely.
+//!
+//!A market can also settle its trades in another base good, through `BVCConfig::base_good` (eur by default). The base good takes the place of `eur` in the rules above: it keeps its default exchange rate, it is excluded from the `mean`, it is never marked as Exported or Imported, and its reserve is the one protected by the `20%` floor. As the `Market` trait requires, prices, budget, bids and offers are still expressed in `eur`, and traders still pay and get paid in `eur`: the market converts it from and into the base good at its default exchange rate. When `eur` is not the base good it is still exchanged 1:1, with both exchange rates pinned at `1.0` and no volume tier. The base good must be part of the goods table, no good can appear twice in it, every default exchange rate must be positive and finite, and `eur` always keeps a default exchange rate of `1.0`: building a market with any other table panics.
+//!
 //!## Price fluctuation:
 //!
 //!> **Premise**: `eur` always has a 1:1 conversion rate
@@ -63,6 +73,30 @@
 //!  
 //!- If the trader wants to buy more than `50%` of a certain good, the market will apply a `3.5%` discount on the `buy price` indiscriminately.  
 //!
+//!### Pricing strategies:
+//!
+//!The rules above are implemented by `TieredPricing`, the default `PricingStrategy`. A market built with `BVCMarket::with_config` can delegate its prices to any other strategy, for example:
+//!
+//!- `LogarithmicPricing` := logarithmic market maker curve, `price = (1 + liquidity * ln(mean / toEur(goodQty))) * defaultPrice`, bounded by `[90%,110%]` of the default price.
+//!
+//!- `TieredPricing { curve: DeflationCurve::Linear, ..Default::default() }` (or `DeflationCurve::Exponential`) := same prices as the tiers above at `mean`, `105%`, `110%`, `130%` and `160%` of the mean, but interpolated (linearly or exponentially) between them, so that the price decreases continuously with the quantity instead of jumping at the thresholds.
+//!
+//!With `quote_mode: QuoteMode::Integrated` the `buy price` of an order is no longer the current exchange rate times the quantity: the exchange rate is integrated over the quantity removed from the market, so that every unit pays the price of the inventory level it leaves behind. Volume discounts are then applied as usual.
+//!
+//!`TieredPricing` can also apply volume tiers to `lock sell`, by setting `sell_volume_tiers: VolumeTier::default_sell_tiers()`. These tiers only apply to goods whose quantity is over the `mean`:
+//!
+//!- If the trader wants to sell a quantity in range `[25%,50%)` of what the market holds of that good, the market will pay `0.5%` less than the `sell price`.
+//!
+//!- If the trader wants to sell more than `50%` of what the market holds of that good, the market will pay `1%` less than the `sell price`.
+//!
+//!The `sell price` of `TieredPricing` can also stop following the `buy price`, by setting `sell_spread: SellSpread::inventory_aware()`: it starts from `99%` of the default price, decreases by up to `3%` as the good grows over the `mean` (the whole `3%` is reached at `60%` over the mean), and increases by up to `5%` as the good falls from the `mean` to `25%` of its initial quantity. In any case it never goes over `99.9%` of the `buy price`. `SellSpread::Custom` accepts any other spread function.
+//!
+//!With `sell_spread: SellSpread::volatility()` the `sell price` reacts to the recent activity instead: every `lock buy`, `lock sell`, `buy`, `sell` and every event received from other markets in the last **12** days widens the spread between `buy price` and `sell price` by `0.1%`, starting from a floor of `0.5%` in quiet periods, up to `3%`.
+//!
+//!Prices are normally updated only when a good is traded. With `idle_decay: Some(IdleDecay { idle_days, rate, target })`, every day after `idle_days` days without trades of a good, its `buy price` covers `rate` of its distance to the target, which is either the default exchange rate (`DecayTarget::DefaultExchangeRate`) or the last buy exchange rate seen in the `Bought` and `LockedBuy` events of the other markets (`DecayTarget::CompetitorPrices`). The decay is kept until the good is traded again, so the re-prices caused by a live mean or by a rebalance still apply it, and so do the integrated quotes and the price breakdowns.
+//!
+//!The `mean` is computed once, on the initial quantities. With `mean_mode: MeanMode::Live` it is recomputed from the current quantities every time goods are locked, bought, sold or rebalanced, and every good is re-priced against the new value.
+//!
 //!## Locks
 //!
 //!The following rules are applied:  
@@ -75,6 +109,20 @@
 //!
 //!- Locks will expire after **12** days, so that a trader can open at most **4** between buy and sell lock transactions over 3 different markets.
 //!
+//!- A lock can also be redeemed in several partial instalments with `buy_partial` and `sell_partial`: each instalment receives the proportional share of the locked good (or eur), while the remainder keeps its original lock time and still expires after **12** days. The lock is closed only once the whole pre-agreed price (or quantity) has been handed over, and an instalment never leaves less than `0.01%` of it still due.
+//!
+//!- Every lock gets its own token, made unique by a per market issue counter and unguessable by 128 random bits, so two locks of the same trader in the same day never collide.
+//!
+//!- Locks remember the trader who opened them: `buy_as`, `buy_partial_as`, `sell_as` and `sell_partial_as` redeem a lock only on behalf of its owner, and fail with `RedeemError::NotLockOwner` otherwise.
+//!
+//!- Every token is tracked as issued, redeemed, expired or cancelled (`token_state`); closed tokens are remembered for **48** days, up to **1024** of them, then pruned. An open lock can be given back with `cancel_lock` by its owner.
+//!
+//!- Open locks can be inspected with `open_buy_locks`, `open_sell_locks` and `lock_info`, which report the remaining quantity, the agreed price, the lock day and the days left before expiry. `open_buy_locks` and `open_sell_locks` only list the locks of the given trader, so that nobody can learn (and redeem) the tokens of the others.
+//!
+//!- Instead of racing `lock buy` (or `lock sell`) against the price changes of the following days, a trader can ask for a quote with `quote_buy` (or `quote_sell`): it reports the total price, the exchange rate per unit and the volume tier applied, and it can be turned into a lock at the quoted price with `lock_buy_quote` (or `lock_sell_quote`) until the end of the second day after it was issued. At most **64** quotes are kept at the same time, the ones closest to expiry are dropped first.
+//!
+//!- To debug a refused bid, `explain_buy` (or `explain_sell`) breaks a price down: default exchange rate, value of the good against the `mean`, price tier (inflation, default or deflation tier), current exchange rate, volume tier with its multiplier, and the final price.
+//!
 //!## Good conversion:
 //!
 //!The logic is trying to equalize good quantities, but not always, to avoid conflicts with the discount logic applyed in the price fluctuation.
@@ -103,7 +151,19 @@
 //!
 //!  and then sum it to the `suffering` good.
 //!
-//!> Note: every 24 days the market will reset the Exported/Imported status for each good.
+//!The conversion is delegated to a `RebalanceStrategy`, which plans the transfers: the algorithm above is `MeanBalancing`, the default one. Through `BVCConfig::rebalance` a market can instead use `NoRebalance`, `TargetWeights { weights }` (moves value towards a target share of the total value for every weighted good) or `EurReserve { share }` (only converts from or into eur, to keep it at `share` of the total value, `30%` by default).
+//!
+//!Conversions are free by default. With `rebalance_fee` set in `BVCConfig`, that share of the eur value of every conversion is lost, as a real currency exchange would charge it (it must be in `[0, 1)`, or building the market panics), and the total lost so far is reported by `rebalance_fees_paid`.
+//!
+//!The random trigger can be replaced through `BVCConfig::rebalance_schedule`, so that rebalances are predictable: `RebalanceSchedule::Every { days }` rebalances every `days` days (building a market with `days: 0` panics), while `RebalanceSchedule::Threshold { imbalance }` rebalances as soon as the eur value of a good is further than `imbalance` times the mean from the mean. `RebalanceSchedule::Random { probability }` is the default.
+//!
+//!Whatever the strategy, a conversion never takes a good below `25%` of its initial quantity, nor `eur` below `20%` of its initial quantity, the same floors applied to `lock buy` and `lock sell`. Goods held by open locks are never converted.
+//!
+//!After a rebalance every converted good (except `eur`) is re-priced at once, instead of keeping its old exchange rates until its next trade. The events of `Notifiable` only describe trades, so the resulting price changes are reported to the observers registered with `add_price_observer`, as `PriceChange`s with the old and new exchange rates.
+//!
+//!The imported/exported status of every good, with the day it was set and the day it will be reset, is reported by `trade_statuses`, while `rebalance_history` lists the last **256** conversions (day, goods, eur value moved and fee paid).
+//!
+//!> Note: **24** days after a good is marked as Exported or Imported, its status is reset, whether or not a rebalance occurs on that day. A new mark restarts the count.
 //!
 //!## Event reaction
 //!
@@ -111,14 +171,21 @@
 
 #[macro_use]
 mod log_formatter;
+mod config;
+mod goods;
+mod price_observer;
+mod pricing;
+mod quotes;
+mod rebalance;
+mod token_registry;
 
 use chrono::Utc;
 use core::panic;
 use rand::Rng;
-use rand::{seq::SliceRandom, thread_rng};
+use rand::thread_rng;
 use std::{
     cell::RefCell,
-    collections::{HashMap, HashSet},
+    collections::{HashMap, VecDeque},
     fs::{File, OpenOptions},
     io::prelude::*,
     rc::Rc,
@@ -141,6 +208,21 @@ use unitn_market_2022::{
         SellError,
     },
 };
+pub use config::BVCConfig;
+pub use goods::{AllocationProfile, AllocationStep, GoodSpec, GoodsTable};
+pub use price_observer::{PriceChange, PriceObserver};
+pub use pricing::{
+    DecayTarget, DeflationCurve, IdleDecay, LogarithmicPricing, MeanMode, PriceTier, PricingContext,
+    PricingStrategy, QuoteMode, SellSpread, TieredPricing, VolumeTier,
+};
+use pricing::integrated_buy_cost;
+pub use quotes::{PriceBreakdown, Quote, QuoteLockError};
+pub use rebalance::{
+    EurReserve, MeanBalancing, NoRebalance, RebalanceGood, RebalanceRecord, RebalanceSchedule,
+    RebalanceStrategy, TargetWeights, TradeStatus, Transfer,
+};
+pub use token_registry::{TokenRecord, TokenState};
+use token_registry::TokenRegistry;
 use KindOfTrade::{Exported, Imported, Unknown};
 use TimeEnabler::{Skip, Use};
 
@@ -153,6 +235,13 @@ const MAX_LOCK_SELL_NUM: u8 = 4;
 const MINIMUM_GOOD_QUANTITY_PERCENTAGE: f32 = 0.25;
 const MINIMUM_EUR_QUANTITY_PERCENTAGE: f32 = 0.20;
 const BUY_TO_SELL_PERCENTAGE: f32 = 0.99;
+const PARTIAL_REDEEM_TOLERANCE: f32 = 0.0001;
+const DEFAULT_TOKEN_RETENTION_DAYS: u64 = 4 * MAX_LOCK_TIME;
+const DEFAULT_MAX_RETIRED_TOKENS: usize = 1024;
+const DEFAULT_ACTIVITY_WINDOW_DAYS: u64 = MAX_LOCK_TIME;
+const QUOTE_VALIDITY_DAYS: u64 = 2;
+const MAX_OPEN_QUOTES: usize = 64;
+const MAX_REBALANCE_HISTORY: usize = 256;
 
 //Good initialization constants
 const EUR_LOWER_BOUND_INIT_PERCENTAGE: f32 = 0.25;
@@ -161,13 +250,19 @@ const SECOND_GOOD_LOWER_BOUND_INIT_PERCENTAGE: f32 = 0.30;
 const SECOND_GOOD_UPPER_BOUND_INIT_PERCENTAGE: f32 = 0.36;
 const THIRD_GOOD_LOWER_BOUND_INIT_PERCENTAGE: f32 = 0.45;
 const THIRD_GOOD_UPPER_BOUND_INIT_PERCENTAGE: f32 = 0.55;
+const EUR_HEAVY_LOWER_BOUND_INIT_PERCENTAGE: f32 = 0.50;
+const EUR_HEAVY_UPPER_BOUND_INIT_PERCENTAGE: f32 = 0.60;
+const SPECIALIST_GOOD_LOWER_BOUND_INIT_PERCENTAGE: f32 = 0.60;
+const SPECIALIST_GOOD_UPPER_BOUND_INIT_PERCENTAGE: f32 = 0.70;
+const SPECIALIST_EUR_LOWER_BOUND_INIT_PERCENTAGE: f32 = 0.50;
+const SPECIALIST_EUR_UPPER_BOUND_INIT_PERCENTAGE: f32 = 0.60;
 
 //Reverse exchange rates constants
 const DEFAULT_USD_EUR_EXCHANGE_RATE: f32 = 1.0 / DEFAULT_EUR_USD_EXCHANGE_RATE;
 const DEFAULT_YEN_EUR_EXCHANGE_RATE: f32 = 1.0 / DEFAULT_EUR_YEN_EXCHANGE_RATE;
 const DEFAULT_YUAN_EUR_EXCHANGE_RATE: f32 = 1.0 / DEFAULT_EUR_YUAN_EXCHANGE_RATE;
 
-//Quantity bounds and price discount constants to apply different price schemes + Lock buy quantity discounts
+//Quantity bounds and price discount constants to apply different price schemes + Lock buy and lock sell quantity discounts
 const MAX_INFLATION_PRICE_INCREASE_PERCENTAGE: f32 = 0.1;
 const DEFAULT_PRICE_LOWER_BOUND_QTY_PERCENTAGE: f32 = 1.0;
 const FIRST_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE: f32 = 1.05;
@@ -186,6 +281,10 @@ const THIRD_LOCK_BUY_DISCOUNT_LOWER_BOUND_QTY: f32 = 0.40;
 const THIRD_LOCK_BUY_DISCOUNT: f32 = 0.975;
 const MAX_LOCK_BUY_DISCOUNT_LOWER_BOUND_QTY: f32 = 0.50;
 const MAX_LOCK_BUY_DISCOUNT: f32 = 0.965;
+const FIRST_LOCK_SELL_DISCOUNT_LOWER_BOUND_QTY: f32 = 0.25;
+const FIRST_LOCK_SELL_DISCOUNT: f32 = 0.995;
+const MAX_LOCK_SELL_DISCOUNT_LOWER_BOUND_QTY: f32 = 0.50;
+const MAX_LOCK_SELL_DISCOUNT: f32 = 0.99;
 
 //Good fluctuation constants
 const PROBABILITY_OF_REBALANCE: f32 = 0.15;
@@ -207,13 +306,30 @@ pub struct BVCMarket {
     oldest_lock_buy_time: TimeEnabler, //used to avoid iterating the map when useless, set to Skip to ignore
     oldest_lock_sell_time: TimeEnabler, //used to avoid iterating the map when useless, set to Skip to ignore
     mean: f32,
+    mean_mode: MeanMode,
     active_buy_locks: u8,
     active_sell_locks: u8,
     good_data: HashMap<GoodKind, GoodInfo>,
+    base: GoodKind, // good in which the market settles its trades, eur by default
+    default_rates: HashMap<GoodKind, f32>, // default exchange rates of the goods traded, from the goods table
     buy_locks: HashMap<String, LockBuyGood>,
     sell_locks: HashMap<String, LockSellGood>,
     subscribers: Vec<Box<dyn Notifiable>>,
-    expired_tokens: HashSet<String>,
+    price_observers: Vec<Box<dyn PriceObserver>>,
+    token_registry: TokenRegistry,
+    pricing: Box<dyn PricingStrategy>,
+    quote_mode: QuoteMode,
+    activity: VecDeque<u64>, // days of the recent trades, oldest first
+    activity_window_days: u64,
+    idle_decay: Option<IdleDecay>,
+    rebalance: Box<dyn RebalanceStrategy>,
+    rebalance_schedule: RebalanceSchedule,
+    rebalance_fee: f32,
+    rebalance_fees_paid: f32, // eur value lost in the rebalance conversions
+    rebalance_history: VecDeque<RebalanceRecord>, // oldest first
+    competitor_rates: HashMap<GoodKind, f32>, // last exchange rates seen in the events of other markets
+    quotes: HashMap<String, Quote>,
+    issued_tokens: u64, // never reset, so that no token can be issued twice
     log_file: File,
 }
 
@@ -223,8 +339,9 @@ enum TimeEnabler {
     Skip,
 }
 
-#[derive(PartialEq)]
-enum KindOfTrade {
+/// Direction of the last rebalancing a good took part in, reset 24 days after it is set
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KindOfTrade {
     Exported,
     Imported,
     Unknown,
@@ -236,20 +353,68 @@ struct GoodInfo {
     sell_exchange_rate: f32,
     initialization_qty: f32,
     kind_of_trade: KindOfTrade,
+    kind_of_trade_day: u64,
+    last_trade_time: u64,
+    idle_decay_progress: f32, // share of the way to the decay target covered since the last trade
 }
 
 struct LockBuyGood {
     locked_good: Good,
     buy_price: f32,
     lock_time: u64,
+    trader_name: String,
 }
 
 #[derive(Clone)]
 struct LockSellGood {
-    locked_eur: Good,
+    locked_base: Good,
     receiving_good_qty: f32,
     locked_kind: GoodKind,
     lock_time: u64,
+    trader_name: String,
+}
+
+/// Error returned by the redemptions that verify the identity of the trader (`buy_as`, `sell_as`, ...)
+#[derive(Debug)]
+pub enum RedeemError<E> {
+    /// The lock exists, but it was opened by a different trader
+    NotLockOwner { token: String, trader_name: String },
+    /// The lock is not open anymore, because it has already been redeemed or cancelled
+    Closed { token: String, state: TokenState },
+    /// Any error that the unchecked redemption would return
+    Market(E),
+}
+
+/// Side of a lock, seen from the trader
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LockSide {
+    Buy,
+    Sell,
+}
+
+/// Read only snapshot of an open lock
+#[derive(Debug, Clone)]
+pub struct LockView {
+    pub token: String,
+    pub side: LockSide,
+    /// Kind of the good bought from (or sold to) the market
+    pub kind: GoodKind,
+    /// Quantity of that good still to be exchanged
+    pub quantity: f32,
+    /// Eur still to be exchanged, as agreed when locking
+    pub agreed_price: f32,
+    pub lock_day: u64,
+    /// Days in which the lock can still be redeemed, `0` means this is the last one
+    pub remaining_days: u64,
+}
+
+/// Error returned by `cancel_lock`
+#[derive(Debug)]
+pub enum CancelLockError {
+    /// The lock exists, but it was opened by a different trader
+    NotLockOwner { token: String, trader_name: String },
+    /// The token does not belong to an open lock, `state` tells why
+    NotOpen { token: String, state: TokenState },
 }
 
 impl BVCMarket {
@@ -261,114 +426,144 @@ impl BVCMarket {
     }
 
     fn update_locks(&mut self) {
-        let mut update_kind_price: Option<GoodKind> = None;
-
-        // * Remove buy locks
-        match &self.oldest_lock_buy_time {
-            Use(oldest, token) if oldest + MAX_LOCK_TIME < self.time => {
-                if CHECK_IF_LOCK_BUY_DROPS {
-                    eprintln!("Discard of oldest lock buy is occurring");
-                }
-                let lock = self.buy_locks.get(token).unwrap();
-                let good = self
-                    .good_data
-                    .get_mut(&lock.locked_good.get_kind())
-                    .unwrap();
-                match good.info.merge(lock.locked_good.clone()) {
-                    Ok(_) => (),
-                    Err(e) => panic!(
-                        "Different kind of goods in merge attempt @update_locks, details: {:?}",
-                        e
-                    ),
-                }
-                update_kind_price = Some(lock.locked_good.get_kind());
-                self.buy_locks.remove(token);
-                self.expired_tokens.insert(token.clone());
-                self.active_buy_locks -= 1;
-                self.oldest_lock_buy_time = if self.buy_locks.len() == 0 {
-                    Skip
-                } else {
-                    let mut new_oldest = Skip;
-                    for (token, good) in &self.buy_locks {
-                        match new_oldest {
-                            Use(time, _) if good.lock_time < time  => new_oldest = Use(good.lock_time, token.clone()),
-                            Skip => new_oldest = Use(good.lock_time, token.clone()),
-                            _ => (),
-                        }
+        // * Remove buy locks, all the ones past their last valid day
+        loop {
+            let expired_buy_token = match &self.oldest_lock_buy_time {
+                Use(oldest, token) if oldest + MAX_LOCK_TIME < self.time => Some(token.clone()),
+                Use(oldest, token) => {
+                    if CHECK_IF_LOCK_BUY_DROPS {
+                        eprintln!(
+                            "Oldest token: {} with time: {} ; current time: {}",
+                            token, oldest, self.time
+                        );
                     }
-                    new_oldest
+                    None
                 }
+                _ => None,
+            };
+
+            let token = match expired_buy_token {
+                Some(token) => token,
+                None => break,
+            };
+            if CHECK_IF_LOCK_BUY_DROPS {
+                eprintln!("Discard of oldest lock buy is occurring");
             }
-            Use(oldest, token) => {
-                if CHECK_IF_LOCK_BUY_DROPS {
-                    eprintln!(
-                        "Oldest token: {} with time: {} ; current time: {}",
-                        token, oldest, self.time
-                    );
-                }
+            let kind = self.release_buy_lock(&token);
+            self.token_registry
+                .retire(&token, TokenState::Expired, self.time);
+            if kind != self.base {
+                self.update_good_price(kind);
             }
-            _ => (),
         }
 
-        update_kind_price = match update_kind_price {
-            Some(kind) if kind != GoodKind::EUR => {
-                self.update_good_price(kind);
-                None
+        // * Remove sell locks, all the ones past their last valid day
+        loop {
+            let expired_sell_token = match &self.oldest_lock_sell_time {
+                Use(oldest, token) if oldest + MAX_LOCK_TIME < self.time => Some(token.clone()),
+                Use(oldest, token) => {
+                    if CHECK_IF_LOCK_SELL_DROPS {
+                        eprintln!(
+                            "Oldest token: {} with time: {} ; current time: {}",
+                            token, oldest, self.time
+                        );
+                    }
+                    None
+                }
+                _ => None,
+            };
+
+            let token = match expired_sell_token {
+                Some(token) => token,
+                None => break,
+            };
+            if CHECK_IF_LOCK_SELL_DROPS {
+                eprintln!("Discard of oldest lock sell is occurring");
             }
-            _ => None,
-        };
+            self.release_sell_lock(&token);
+            self.token_registry
+                .retire(&token, TokenState::Expired, self.time);
+        }
 
-        // * Remove sell locks
-        match &self.oldest_lock_sell_time {
-            Use(oldest, token) if oldest + MAX_LOCK_TIME < self.time => {
-                if CHECK_IF_LOCK_SELL_DROPS {
-                    eprintln!("Discard of oldest lock sell is occurring");
-                }
-                let lock = self.sell_locks.get(token).unwrap();
-                let good = self.good_data.get_mut(&GoodKind::EUR).unwrap();
-
-                match good.info.merge(lock.locked_eur.clone()) {
-                    Ok(_) => (),
-                    Err(e) => panic!(
-                        "Different kind of goods in merge attempt @update_locks, details: {:?}",
-                        e
-                    ),
-                }
+        self.token_registry.prune(self.time);
+    }
 
-                self.sell_locks.remove(token);
-                self.expired_tokens.insert(token.clone());
-                self.active_sell_locks -= 1;
-                self.oldest_lock_sell_time = if self.sell_locks.len() == 0 {
-                    Skip
-                } else {
-                    let mut new_oldest = Skip;
-                    for (token, good) in &self.sell_locks {
-                        match new_oldest {
-                            Use(time, _) if good.lock_time < time => new_oldest = Use(good.lock_time, token.clone()),
-                            Skip => new_oldest = Use(good.lock_time, token.clone()),
-                            _ => (),
-                        }
+    // * Removes a buy lock, keeping the oldest lock and the lock counter consistent
+    fn remove_buy_lock(&mut self, token: &String) -> LockBuyGood {
+        let lock = self.buy_locks.remove(token).unwrap();
+
+        // * Updates the oldest lock if removed one was the oldest
+        match &self.oldest_lock_buy_time {
+            Use(_,tok) if tok == token => {
+                let mut oldest_lock = Skip;
+                for (tk,good_lock) in &self.buy_locks{
+                    let cmp_time = good_lock.lock_time;
+                    match oldest_lock {
+                        Use(time,_) if cmp_time < time => oldest_lock = Use(cmp_time,tk.clone()),
+                        Skip => oldest_lock = Use(cmp_time,tk.clone()),
+                        _ => (),
                     }
-                    new_oldest
-                }
-            }
-            Use(oldest, token) => {
-                if CHECK_IF_LOCK_SELL_DROPS {
-                    eprintln!(
-                        "Oldest token: {} with time: {} ; current time: {}",
-                        token, oldest, self.time
-                    );
                 }
-            }
+                self.oldest_lock_buy_time = oldest_lock;
+            },
             _ => (),
         }
 
-        match update_kind_price {
-            Some(kind) if kind != GoodKind::EUR => self.update_good_price(kind),
+        self.active_buy_locks -= 1;
+        lock
+    }
+
+    // * Removes a sell lock, keeping the oldest lock and the lock counter consistent
+    fn remove_sell_lock(&mut self, token: &String) -> LockSellGood {
+        let lock = self.sell_locks.remove(token).unwrap();
+
+        // * Updates the oldest lock if removed one was the oldest
+        match &self.oldest_lock_sell_time {
+            Use(_,tok) if tok == token => {
+                let mut oldest_lock = Skip;
+                for (tk,good_lock) in &self.sell_locks{
+                    let cmp_time = good_lock.lock_time;
+                    match oldest_lock {
+                        Use(time,_) if cmp_time < time => oldest_lock = Use(cmp_time,tk.clone()),
+                        Skip => oldest_lock = Use(cmp_time,tk.clone()),
+                        _ => (),
+                    }
+                }
+                self.oldest_lock_sell_time = oldest_lock;
+            },
             _ => (),
-        };
+        }
+
+        self.active_sell_locks -= 1;
+        lock
+    }
+
+    // * Gives the locked good back to the market, returns its kind so that the caller can update its price
+    fn release_buy_lock(&mut self, token: &String) -> GoodKind {
+        let lock = self.remove_buy_lock(token);
+        let kind = lock.locked_good.get_kind();
+        let good = self.good_data.get_mut(&kind).unwrap();
+        match good.info.merge(lock.locked_good) {
+            Ok(_) => (),
+            Err(e) => panic!(
+                "Different kind of goods in merge attempt @release_buy_lock, details: {:?}",
+                e
+            ),
+        }
+        kind
+    }
 
-        return;
+    // * Gives the locked base good back to the market
+    fn release_sell_lock(&mut self, token: &String) {
+        let lock = self.remove_sell_lock(token);
+        let good = self.good_data.get_mut(&self.base).unwrap();
+        match good.info.merge(lock.locked_base) {
+            Ok(_) => (),
+            Err(e) => panic!(
+                "Different kind of goods in merge attempt @release_sell_lock, details: {:?}",
+                e
+            ),
+        }
     }
 
     fn increment_time(&mut self) {
@@ -398,463 +593,655 @@ impl BVCMarket {
                 for (_, good) in &mut self.sell_locks {
                     good.lock_time -= oldest;
                 }
-                self.expired_tokens.clear();
+            }
+            self.token_registry.shift(oldest);
+            for day in self.activity.iter_mut() {
+                *day = day.saturating_sub(oldest);
+            }
+            for good_info in self.good_data.values_mut() {
+                good_info.last_trade_time = good_info.last_trade_time.saturating_sub(oldest);
+                good_info.kind_of_trade_day = good_info.kind_of_trade_day.saturating_sub(oldest);
+            }
+            for record in self.rebalance_history.iter_mut() {
+                record.day = record.day.saturating_sub(oldest);
+            }
+            for quote in self.quotes.values_mut() {
+                quote.valid_until = quote.valid_until.saturating_sub(oldest);
             }
             self.time -= oldest;
         }
 
         self.update_locks();
         self.time += 1;
+        self.prune_quotes();
+        self.prune_activity();
+        self.decay_idle_prices();
+        self.reset_kinds_of_trade();
         self.fluctuate_quantity();
+        self.update_mean();
     }
 
-    // * This will try to rebalance all good quantities
-    fn fluctuate_quantity(&mut self) {
-        let mut rng = thread_rng();
-
-        if rng.gen_range(0.0, 1.0) < PROBABILITY_OF_REBALANCE {
-            let mut good_transformed_quantities: HashMap<GoodKind, f32> = HashMap::new();
+    // * With a live mean, recomputes it from the current quantities and re-prices every good if it moved
+    fn update_mean(&mut self) {
+        if self.mean_mode != MeanMode::Live {
+            return;
+        }
 
-            // * Calculate the mean to determine which good is suffering and which good is not
-            let mut mean: f32 = 0.0;
-            for (kind, good_info) in &mut self.good_data {
-                if self.time % DURATION_OF_CHOSEN_KIND_OF_TRADE == 0 {
-                    good_info.kind_of_trade = Unknown;
-                }
-                match *kind {
-                    GoodKind::EUR => {
-                        good_transformed_quantities.insert(GoodKind::EUR, good_info.info.get_qty())
-                    }
-                    GoodKind::USD => good_transformed_quantities.insert(
-                        GoodKind::USD,
-                        good_info.info.get_qty() * DEFAULT_USD_EUR_EXCHANGE_RATE,
-                    ),
-                    GoodKind::YEN => good_transformed_quantities.insert(
-                        GoodKind::YEN,
-                        good_info.info.get_qty() * DEFAULT_YEN_EUR_EXCHANGE_RATE,
-                    ),
-                    GoodKind::YUAN => good_transformed_quantities.insert(
-                        GoodKind::YUAN,
-                        good_info.info.get_qty() * DEFAULT_YUAN_EUR_EXCHANGE_RATE,
-                    ),
-                };
-                mean += good_transformed_quantities[kind];
+        let (mut total, mut goods) = (0.0, 0.0);
+        for (kind, good_info) in &self.good_data {
+            if *kind != self.base {
+                total += good_info.info.get_qty() * self.default_price(*kind);
+                goods += 1.0;
             }
-            mean /= 4.0;
+        }
+        let mean = if goods > 0.0 { total / goods } else { 0.0 };
 
-            if CHECK_IF_FLUCTUATION_OCCURS {
-                eprintln!("Fluctuation is occurring with real time mean: {}", mean);
+        if mean != self.mean {
+            if SHOW_MEAN {
+                eprintln!("live_mean : {}", mean);
             }
+            self.mean = mean;
+            self.update_all_prices();
+        }
+    }
 
-            while let Some((
-                (suffering_good_qty, suffering_good_kind),
-                (eligible_good_qty, eligible_good_kind),
-            )) = self.find_goods_to_balance(&good_transformed_quantities, mean)
-            {
-                if CHECK_IF_FIND_GOODS_TO_FLUCTUATE {
-                    eprintln!("Before trading -> eligible good: {} with qty: {} ; suffering good: {} with qty: {}",
-                    eligible_good_kind, eligible_good_qty, suffering_good_kind, suffering_good_qty)
-                }
+    fn update_all_prices(&mut self) {
+        let kinds: Vec<GoodKind> = self
+            .good_data
+            .keys()
+            .filter(|kind| **kind != self.base)
+            .copied()
+            .collect();
+        for kind in kinds {
+            self.update_good_price(kind);
+        }
+    }
 
-                if suffering_good_kind != GoodKind::EUR {
-                    self.good_data
-                        .get_mut(&suffering_good_kind)
-                        .unwrap()
-                        .kind_of_trade = Imported;
-                }
-                if eligible_good_kind != GoodKind::EUR {
-                    self.good_data
-                        .get_mut(&eligible_good_kind)
-                        .unwrap()
-                        .kind_of_trade = Exported;
-                }
+    // * Remembers the last day a good has been traded, used by the idle price decay
+    fn touch_good(&mut self, kind: GoodKind) {
+        if let Some(good_info) = self.good_data.get_mut(&kind) {
+            good_info.last_trade_time = self.time;
+            good_info.idle_decay_progress = 0.0;
+        }
+    }
 
-                let distance_to_fill =
-                    f32::min(mean - suffering_good_qty, eligible_good_qty - mean);
-                let split_from_eligible_good = match eligible_good_kind {
-                    GoodKind::EUR => distance_to_fill,
-                    GoodKind::USD => distance_to_fill * DEFAULT_EUR_USD_EXCHANGE_RATE,
-                    GoodKind::YEN => distance_to_fill * DEFAULT_EUR_YEN_EXCHANGE_RATE,
-                    GoodKind::YUAN => distance_to_fill * DEFAULT_EUR_YUAN_EXCHANGE_RATE,
-                };
-                let merge_to_suffering_good = match suffering_good_kind {
-                    GoodKind::EUR => distance_to_fill,
-                    GoodKind::USD => distance_to_fill * DEFAULT_EUR_USD_EXCHANGE_RATE,
-                    GoodKind::YEN => distance_to_fill * DEFAULT_EUR_YEN_EXCHANGE_RATE,
-                    GoodKind::YUAN => distance_to_fill * DEFAULT_EUR_YUAN_EXCHANGE_RATE,
-                };
+    // * Moves the prices of the goods that nobody traded for a while towards the decay target.
+    // * The progress is kept per good, so that every later re-price (live mean, rebalance) still applies it
+    fn decay_idle_prices(&mut self) {
+        let decay = match self.idle_decay {
+            Some(decay) => decay,
+            None => return,
+        };
 
-                self.good_data
-                    .get_mut(&eligible_good_kind)
-                    .unwrap()
-                    .info
-                    .split(split_from_eligible_good);
-                self.good_data
-                    .get_mut(&suffering_good_kind)
-                    .unwrap()
-                    .info
-                    .merge(Good::new(suffering_good_kind, merge_to_suffering_good));
-
-                *good_transformed_quantities
-                    .get_mut(&eligible_good_kind)
-                    .unwrap() -= distance_to_fill;
-                *good_transformed_quantities
-                    .get_mut(&suffering_good_kind)
-                    .unwrap() += distance_to_fill;
-
-                if CHECK_IF_FIND_GOODS_TO_FLUCTUATE {
-                    eprintln!("After trading -> eligible good: {} with qty: {} ; suffering good: {} with qty: {}",
-                    eligible_good_kind,good_transformed_quantities[&eligible_good_kind], suffering_good_kind, good_transformed_quantities[&suffering_good_kind])
-                }
-            }
+        let kinds: Vec<GoodKind> = self
+            .good_data
+            .iter()
+            .filter(|(kind, good_info)| {
+                **kind != self.base && good_info.last_trade_time + decay.idle_days < self.time
+            })
+            .map(|(kind, _)| *kind)
+            .collect();
+        for kind in kinds {
+            let good_info = self.good_data.get_mut(&kind).unwrap();
+            good_info.idle_decay_progress += (1.0 - good_info.idle_decay_progress) * decay.rate;
+            self.update_good_price(kind);
         }
     }
 
-    // * Retrieves which good is suffering and which one is eligible to transfer its quantities
-    fn find_goods_to_balance(
-        &self,
-        good_transformed_quantities: &HashMap<GoodKind, f32>,
-        mean: f32,
-    ) -> Option<((f32, GoodKind), (f32, GoodKind))> {
-        let (mut suffering_good, mut eligible_good): (
-            Option<(f32, GoodKind)>,
-            Option<(f32, GoodKind)>,
-        ) = (None, None);
-
-        for (kind, good_info) in &self.good_data {
-            let good_qty = good_transformed_quantities[kind];
-            if good_qty < mean && good_info.kind_of_trade != Exported {
-                suffering_good = match suffering_good {
-                    Some(good) if good_qty < good.0 => Some((good_qty, *kind)),
-                    None => Some((good_qty, *kind)),
-                    _ => suffering_good,
-                };
-            } else if good_qty > mean && good_info.kind_of_trade != Imported {
-                eligible_good = match eligible_good {
-                    Some(good) if good_qty > good.0 => Some((good_qty, *kind)),
-                    None => Some((good_qty, *kind)),
-                    _ => eligible_good,
-                };
+    fn decay_target(&self, kind: GoodKind, target: DecayTarget) -> f32 {
+        match target {
+            DecayTarget::CompetitorPrices if self.competitor_rates.contains_key(&kind) => {
+                self.competitor_rates[&kind]
             }
+            _ => self.default_price(kind),
         }
-
-        suffering_good.zip(eligible_good)
     }
 
-    fn update_good_price(&mut self, kind: GoodKind) {
-        if let Some(good_info) = self.good_data.get_mut(&kind) {
-            let default_price = match kind {
-                GoodKind::USD => DEFAULT_USD_EUR_EXCHANGE_RATE,
-                GoodKind::YEN => DEFAULT_YEN_EUR_EXCHANGE_RATE,
-                GoodKind::YUAN => DEFAULT_YUAN_EUR_EXCHANGE_RATE,
-                GoodKind::EUR => panic!("Eur should not update its price !"),
-            };
-
-            let (good_qty, initial_good_qty) = (
-                good_info.info.get_qty() * default_price,
-                good_info.initialization_qty * default_price,
-            );
-
-            if good_qty < self.mean * DEFAULT_PRICE_LOWER_BOUND_QTY_PERCENTAGE {
-                good_info.buy_exchange_rate = (((1.0
-                    - (good_qty - initial_good_qty * MINIMUM_GOOD_QUANTITY_PERCENTAGE)
-                        / (self.mean - initial_good_qty * MINIMUM_GOOD_QUANTITY_PERCENTAGE))
-                    * MAX_INFLATION_PRICE_INCREASE_PERCENTAGE)
-                    + 1.0)
-                    * default_price
-            } else if good_qty < self.mean * FIRST_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE {
-                good_info.buy_exchange_rate = default_price
-            } else if good_qty < self.mean * SECOND_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE {
-                good_info.buy_exchange_rate = default_price * FIRST_DEFLATION_PRICE_DISCOUNT
-            } else if good_qty < self.mean * THIRD_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE {
-                good_info.buy_exchange_rate = default_price * SECOND_DEFLATION_PRICE_DISCOUNT
-            } else if good_qty < self.mean * MAX_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE {
-                good_info.buy_exchange_rate = default_price * THIRD_DEFLATION_PRICE_DISCOUNT
-            } else {
-                good_info.buy_exchange_rate = default_price * MAX_DEFLATION_PRICE_DISCOUNT
+    // * Buy exchange rate of the pricing strategy, an idle good keeps the part of the way to the decay target
+    // * it has already covered. Shared by the exchange rates, the integrated quotes and the price breakdowns
+    fn decayed_buy_rate(&self, ctx: &PricingContext) -> f32 {
+        let buy_exchange_rate = self.pricing.buy_exchange_rate(ctx);
+        match self.idle_decay {
+            Some(decay) => {
+                let progress = self.good_data[&ctx.kind].idle_decay_progress;
+                let target = self.decay_target(ctx.kind, decay.target);
+                buy_exchange_rate + (target - buy_exchange_rate) * progress
             }
-            good_info.sell_exchange_rate = good_info.buy_exchange_rate * BUY_TO_SELL_PERCENTAGE
-        } else {
-            panic!(
-                "Couldn't find GoodKind key {} in the good_data HashMap",
-                kind
-            )
+            None => buy_exchange_rate,
         }
     }
 
-    // * Notify other markets
-    fn notify_markets(&mut self, event: Event) {
-        for m in &mut self.subscribers {
-            m.on_event(event.clone())
+    // * Price tier of the pricing strategy, with its premium or discount moved by the idle decay
+    fn decayed_price_tier(&self, ctx: &PricingContext) -> PriceTier {
+        let ratio = self.decayed_buy_rate(ctx) / self.pricing.buy_exchange_rate(ctx);
+        match self.pricing.price_tier(ctx) {
+            PriceTier::Inflation { premium } => PriceTier::Inflation {
+                premium: (1.0 + premium) * ratio - 1.0,
+            },
+            PriceTier::Deflation { tier, discount } => PriceTier::Deflation {
+                tier,
+                discount: discount * ratio,
+            },
+            PriceTier::Curve { discount } => PriceTier::Curve {
+                discount: discount * ratio,
+            },
+            price_tier => price_tier,
         }
     }
 
-    fn token(operation: String, trader: String, time: u64) -> String {
-        format!("{}-{}-{}", operation, trader, time)
+    // * Remembers a trade (or an event of another market) seen today, used by the activity driven spreads
+    fn record_activity(&mut self) {
+        self.activity.push_back(self.time);
+        self.refresh_sell_rates();
     }
-}
 
-impl Notifiable for BVCMarket {
-    fn add_subscriber(&mut self, subscriber: Box<dyn Notifiable>) {
-        self.subscribers.push(subscriber);
+    fn prune_activity(&mut self) {
+        let mut pruned = false;
+        while let Some(day) = self.activity.front() {
+            if day + self.activity_window_days > self.time {
+                break;
+            }
+            self.activity.pop_front();
+            pruned = true;
+        }
+        if pruned {
+            self.refresh_sell_rates();
+        }
     }
-    fn on_event(&mut self, _event: Event) {
-        self.increment_time();
-        /* match event.kind {
-            EventKind::Wait => self.increment_time(),
-            _ => (),
-        } */
+
+    // * Recomputes the sell exchange rates from the current buy exchange rates, pinned eur keeps its 1.0
+    fn refresh_sell_rates(&mut self) {
+        let kinds: Vec<GoodKind> = self
+            .good_data
+            .keys()
+            .filter(|kind| **kind != self.base && !self.is_pinned_eur(**kind))
+            .copied()
+            .collect();
+        for kind in kinds {
+            let ctx = self.pricing_context(kind);
+            let good_info = self.good_data.get_mut(&kind).unwrap();
+            good_info.sell_exchange_rate = self
+                .pricing
+                .sell_exchange_rate(good_info.buy_exchange_rate, &ctx);
+        }
     }
-}
 
-impl Market for BVCMarket {
-    fn new_random() -> Rc<RefCell<dyn Market>>
-    where
-        Self: Sized,
-    {
-        let mut max = STARTING_CAPITAL;
-        let (mut eur, mut yen, mut usd, mut yuan): (f32, f32, f32, f32) = (0.0, 0.0, 0.0, 0.0);
-        let mut good_kinds = vec![GoodKind::USD, GoodKind::YUAN, GoodKind::YEN];
-        let mut rng = thread_rng();
+    // * This will try to rebalance all good quantities
+    fn fluctuate_quantity(&mut self) {
+        if self.rebalance_schedule.is_due(self.time, &self.rebalance_goods()) {
+            let goods = self.rebalance_goods();
 
-        eur = rng.gen_range(
-            max * EUR_LOWER_BOUND_INIT_PERCENTAGE,
-            max * EUR_UPPER_BOUND_INIT_PERCENTAGE,
-        );
-        max -= eur;
+            if CHECK_IF_FLUCTUATION_OCCURS {
+                eprintln!("Fluctuation is occurring with goods: {:?}", goods);
+            }
 
-        good_kinds.shuffle(&mut rng);
+            // * Exchange rates of the touched goods before the rebalance
+            let mut old_rates: Vec<(GoodKind, f32, f32)> = Vec::new();
+            for transfer in self.rebalance.plan(&goods) {
+                let rates_before = self.exchange_rates();
+                if self.apply_transfer(transfer) {
+                    for (kind, buy_rate, sell_rate) in rates_before {
+                        let touched = kind == transfer.from || kind == transfer.to;
+                        if touched && !old_rates.iter().any(|rates| rates.0 == kind) {
+                            old_rates.push((kind, buy_rate, sell_rate));
+                        }
+                    }
+                }
+            }
 
-        let mut random_qty = rng.gen_range(
-            max * SECOND_GOOD_LOWER_BOUND_INIT_PERCENTAGE,
-            max * SECOND_GOOD_UPPER_BOUND_INIT_PERCENTAGE,
-        );
-        match good_kinds[0] {
-            GoodKind::USD => usd = random_qty,
-            GoodKind::YEN => yen = random_qty,
-            GoodKind::YUAN => yuan = random_qty,
-            GoodKind::EUR => panic!("Matched EUR which has been already initialized !"),
+            // * Re-price the touched goods, against the new mean if it is live
+            self.update_mean();
+            for (kind, old_buy_exchange_rate, old_sell_exchange_rate) in old_rates {
+                self.update_good_price(kind);
+                let good_info = &self.good_data[&kind];
+                let change = PriceChange {
+                    kind,
+                    old_buy_exchange_rate,
+                    new_buy_exchange_rate: good_info.buy_exchange_rate,
+                    old_sell_exchange_rate,
+                    new_sell_exchange_rate: good_info.sell_exchange_rate,
+                    day: self.time,
+                };
+                self.notify_price_change(change);
+            }
         }
-        max -= random_qty;
+    }
 
-        random_qty = rng.gen_range(
-            max * THIRD_GOOD_LOWER_BOUND_INIT_PERCENTAGE,
-            max * THIRD_GOOD_UPPER_BOUND_INIT_PERCENTAGE,
-        );
-        match good_kinds[1] {
-            GoodKind::USD => usd = random_qty,
-            GoodKind::YEN => yen = random_qty,
-            GoodKind::YUAN => yuan = random_qty,
-            GoodKind::EUR => panic!("Matched EUR which has been already initialized !"),
+    // * Buy and sell exchange rates of the goods except eur
+    fn exchange_rates(&self) -> Vec<(GoodKind, f32, f32)> {
+        self.good_data
+            .iter()
+            .filter(|(kind, _)| **kind != self.base)
+            .map(|(kind, good_info)| {
+                (
+                    *kind,
+                    good_info.buy_exchange_rate,
+                    good_info.sell_exchange_rate,
+                )
+            })
+            .collect()
+    }
+
+    fn notify_price_change(&mut self, change: PriceChange) {
+        if change.old_buy_exchange_rate == change.new_buy_exchange_rate
+            && change.old_sell_exchange_rate == change.new_sell_exchange_rate
+        {
+            return;
         }
-        max -= random_qty;
+        for observer in &mut self.price_observers {
+            observer.on_price_change(change);
+        }
+    }
 
-        match good_kinds[2] {
-            GoodKind::USD => usd = max,
-            GoodKind::YEN => yen = max,
-            GoodKind::YUAN => yuan = max,
-            GoodKind::EUR => panic!("Matched EUR which has been already initialized !"),
+    // * A good is marked as imported or exported for `DURATION_OF_CHOSEN_KIND_OF_TRADE` days,
+    // * whether or not a rebalance occurs when the mark expires
+    fn reset_kinds_of_trade(&mut self) {
+        let time = self.time;
+        for good_info in self.good_data.values_mut() {
+            if good_info.kind_of_trade != Unknown
+                && good_info.kind_of_trade_day + DURATION_OF_CHOSEN_KIND_OF_TRADE <= time
+            {
+                good_info.kind_of_trade = Unknown;
+            }
         }
-        Self::new_with_quantities(
-            eur,
-            yen * DEFAULT_EUR_YEN_EXCHANGE_RATE,
-            usd * DEFAULT_EUR_USD_EXCHANGE_RATE,
-            yuan * DEFAULT_EUR_YUAN_EXCHANGE_RATE,
-        )
     }
 
-    fn new_with_quantities(eur: f32, yen: f32, usd: f32, yuan: f32) -> Rc<RefCell<dyn Market>>
-    where
-        Self: Sized,
-    {
-        let file = OpenOptions::new()
-            .append(true)
-            .create(true)
-            .open(format!("log_{}.txt", NAME))
-            .expect("Unable to create log file !");
+    fn rebalance_goods(&self) -> Vec<RebalanceGood> {
+        self.good_data
+            .iter()
+            .map(|(kind, good_info)| RebalanceGood {
+                kind: *kind,
+                value: good_info.info.get_qty() * self.default_price(*kind),
+                initial_value: good_info.initialization_qty * self.default_price(*kind),
+                floor_value: self.reserve_floor(*kind) * self.default_price(*kind),
+                kind_of_trade: good_info.kind_of_trade,
+                is_base: *kind == self.base,
+            })
+            .collect()
+    }
 
-        let mut market: BVCMarket = BVCMarket {
-            time: 0,
-            oldest_lock_buy_time: Skip,
-            oldest_lock_sell_time: Skip,
-            active_buy_locks: 0,
-            active_sell_locks: 0,
-            mean: (usd * DEFAULT_USD_EUR_EXCHANGE_RATE
-                + yen * DEFAULT_YEN_EUR_EXCHANGE_RATE
-                + yuan * DEFAULT_YUAN_EUR_EXCHANGE_RATE)
-                / 3.0,
-            good_data: HashMap::new(),
-            buy_locks: HashMap::new(),
-            sell_locks: HashMap::new(),
-            subscribers: Vec::new(),
-            log_file: file,
-            expired_tokens: HashSet::new(),
+    // * Quantity the market never goes below, the same floors applied by `get_buy_price` and `get_sell_price`
+    fn reserve_floor(&self, kind: GoodKind) -> f32 {
+        let floor_percentage = if kind == self.base {
+            MINIMUM_EUR_QUANTITY_PERCENTAGE
+        } else {
+            MINIMUM_GOOD_QUANTITY_PERCENTAGE
         };
+        self.good_data[&kind].initialization_qty * floor_percentage
+    }
 
-        if SHOW_MEAN {
-            eprintln!("initialization_mean : {}", market.mean);
+    // * Converts part of a good into another one at the default exchange rates, minus the rebalance fee,
+    // * and marks both goods. Locked goods are not held by the market anymore, so they are never converted,
+    // * and the good never goes below its reserve floor.
+    fn apply_transfer(&mut self, transfer: Transfer) -> bool {
+        let Transfer { from, to, eur_value } = transfer;
+        let available_qty = self.good_data[&from].info.get_qty() - self.reserve_floor(from);
+        let available_value = available_qty * self.default_price(from);
+        let eur_value = eur_value.min(available_value);
+        if from == to || eur_value <= 0.0 {
+            return false;
         }
 
-        market.good_data.insert(
-            GoodKind::EUR,
-            GoodInfo {
-                info: Good::new(GoodKind::EUR, eur),
-                buy_exchange_rate: 1.0,
-                sell_exchange_rate: 1.0,
-                initialization_qty: eur,
-                kind_of_trade: Unknown,
-            },
-        );
-
-        market.good_data.insert(
-            GoodKind::USD,
-            GoodInfo {
-                info: Good::new(GoodKind::USD, usd),
-                buy_exchange_rate: 0.0,
-                sell_exchange_rate: 0.0,
-                initialization_qty: usd,
-                kind_of_trade: Unknown,
-            },
-        );
+        if CHECK_IF_FIND_GOODS_TO_FLUCTUATE {
+            eprintln!(
+                "Before trading -> eligible good: {} with qty: {} ; suffering good: {} with qty: {}",
+                from,
+                self.good_data[&from].info.get_qty(),
+                to,
+                self.good_data[&to].info.get_qty()
+            )
+        }
 
-        market.good_data.insert(
-            GoodKind::YEN,
-            GoodInfo {
-                info: Good::new(GoodKind::YEN, yen),
-                buy_exchange_rate: 0.0,
-                sell_exchange_rate: 0.0,
-                initialization_qty: yen,
-                kind_of_trade: Unknown,
-            },
-        );
+        if to != self.base {
+            let good_info = self.good_data.get_mut(&to).unwrap();
+            good_info.kind_of_trade = Imported;
+            good_info.kind_of_trade_day = self.time;
+        }
+        if from != self.base {
+            let good_info = self.good_data.get_mut(&from).unwrap();
+            good_info.kind_of_trade = Exported;
+            good_info.kind_of_trade_day = self.time;
+        }
 
-        market.good_data.insert(
-            GoodKind::YUAN,
-            GoodInfo {
-                info: Good::new(GoodKind::YUAN, yuan),
-                buy_exchange_rate: 0.0,
-                sell_exchange_rate: 0.0,
-                initialization_qty: yuan,
-                kind_of_trade: Unknown,
-            },
-        );
+        let split_from_eligible_good = eur_value / self.default_price(from);
+        let merge_to_suffering_good =
+            eur_value * (1.0 - self.rebalance_fee) / self.default_price(to);
+        self.good_data
+            .get_mut(&from)
+            .unwrap()
+            .info
+            .split(split_from_eligible_good);
+        self.good_data
+            .get_mut(&to)
+            .unwrap()
+            .info
+            .merge(Good::new(to, merge_to_suffering_good));
+        self.rebalance_fees_paid += eur_value * self.rebalance_fee;
+
+        if self.rebalance_history.len() >= MAX_REBALANCE_HISTORY {
+            self.rebalance_history.pop_front();
+        }
+        self.rebalance_history.push_back(RebalanceRecord {
+            day: self.time,
+            from,
+            to,
+            eur_value,
+            fee: eur_value * self.rebalance_fee,
+        });
+
+        if CHECK_IF_FIND_GOODS_TO_FLUCTUATE {
+            eprintln!(
+                "After trading -> eligible good: {} with qty: {} ; suffering good: {} with qty: {}",
+                from,
+                self.good_data[&from].info.get_qty(),
+                to,
+                self.good_data[&to].info.get_qty()
+            )
+        }
 
-        market.update_good_price(GoodKind::USD);
-        market.update_good_price(GoodKind::YEN);
-        market.update_good_price(GoodKind::YUAN);
-        market.write_on_log_file(log_format_market_init!(NAME, eur, usd, yen, yuan));
+        true
+    }
 
-        Rc::new(RefCell::new(market))
+    fn default_price(&self, kind: GoodKind) -> f32 {
+        self.default_rates[&kind]
     }
 
-    fn new_file(_path: &str) -> Rc<RefCell<dyn Market>>
-    where
-        Self: Sized,
-    {
-        Self::new_random()
+    // * Quantity of the base good worth `eur` eur, at its default exchange rate
+    fn eur_to_base(&self, eur: f32) -> f32 {
+        eur / self.default_price(self.base)
     }
 
-    fn get_name(&self) -> &'static str {
-        NAME
+    // * Eur handed to the traders for a quantity of the base good, at its default exchange rate
+    fn base_to_eur(&self, base: Good) -> Good {
+        if self.base == GoodKind::EUR {
+            base
+        } else {
+            Good::new(GoodKind::EUR, base.get_qty() * self.default_price(self.base))
+        }
     }
 
-    fn get_budget(&self) -> f32 {
-        self.good_data[&GoodKind::EUR].info.get_qty()
+    // * The base good received for the eur paid by the traders, at its default exchange rate
+    fn eur_to_base_good(&self, eur: Good) -> Good {
+        if self.base == GoodKind::EUR {
+            eur
+        } else {
+            Good::new(self.base, self.eur_to_base(eur.get_qty()))
+        }
     }
 
-    fn get_buy_price(&self, kind: GoodKind, quantity: f32) -> Result<f32, MarketGetterError> {
-        if quantity.is_sign_negative() {
-            return Err(MarketGetterError::NonPositiveQuantityAsked);
+    fn pricing_context(&self, kind: GoodKind) -> PricingContext {
+        if let Some(good_info) = self.good_data.get(&kind) {
+            PricingContext {
+                kind,
+                default_price: self.default_price(kind),
+                quantity: good_info.info.get_qty(),
+                initialization_qty: good_info.initialization_qty,
+                mean: self.mean,
+                recent_activity: self.activity.len(),
+            }
+        } else {
+            panic!(
+                "Couldn't find GoodKind key {} in the good_data HashMap",
+                kind
+            )
+        }
+    }
+
+    fn update_good_price(&mut self, kind: GoodKind) {
+        if kind == self.base {
+            panic!("The base good should not update its price !");
         }
 
-        let good_data = &self.good_data[&kind];
+        if self.is_pinned_eur(kind) {
+            let good_info = self.good_data.get_mut(&kind).unwrap();
+            good_info.buy_exchange_rate = 1.0;
+            good_info.sell_exchange_rate = 1.0;
+            return;
+        }
 
-        // * Getting the quantity availability
-        let available_good_qty = good_data.info.get_qty();
-        let quantity_cap = good_data.initialization_qty * MINIMUM_GOOD_QUANTITY_PERCENTAGE;
+        let ctx = self.pricing_context(kind);
+        let buy_exchange_rate = self.decayed_buy_rate(&ctx);
+        let sell_exchange_rate = self.pricing.sell_exchange_rate(buy_exchange_rate, &ctx);
 
-        if available_good_qty - quantity < quantity_cap {
-            return Err(MarketGetterError::InsufficientGoodQuantityAvailable {
-                requested_good_kind: kind,
-                requested_good_quantity: quantity,
-                available_good_quantity: available_good_qty,
-            });
+        let good_info = self.good_data.get_mut(&kind).unwrap();
+        good_info.buy_exchange_rate = buy_exchange_rate;
+        good_info.sell_exchange_rate = sell_exchange_rate;
+    }
+
+    // * Notify other markets
+    fn notify_markets(&mut self, event: Event) {
+        for m in &mut self.subscribers {
+            m.on_event(event.clone())
         }
+    }
 
-        if CHECK_BUY_PRICE {
-            eprintln!(
-                "Requested good {} with qty: {}, and exchange rate: {}",
-                kind, quantity, good_data.buy_exchange_rate
-            );
+    // * The issue counter makes every id unique, while the 128 random bits drawn from the thread local CSPRNG
+    // * make it unguessable, even knowing the trader name and the time of the lock
+    fn random_id(&mut self, operation: &str) -> String {
+        let mut rng = thread_rng();
+        self.issued_tokens += 1;
+        format!(
+            "{}-{:x}-{:016x}{:016x}",
+            operation,
+            self.issued_tokens,
+            rng.gen::<u64>(),
+            rng.gen::<u64>()
+        )
+    }
+
+    fn token(&mut self, operation: &str) -> String {
+        let token = self.random_id(operation);
+        self.token_registry.issue(&token, self.time);
+        token
+    }
+
+    // * A quote can be used until the end of its last valid day
+    fn prune_quotes(&mut self) {
+        let time = self.time;
+        self.quotes.retain(|_, quote| quote.valid_until >= time);
+    }
+
+    fn store_quote(&mut self, quote: Quote) {
+        // * Keep the memory bounded by dropping the quote closest to its expiry
+        if self.quotes.len() >= MAX_OPEN_QUOTES {
+            let closest_to_expiry = self
+                .quotes
+                .values()
+                .min_by_key(|quote| quote.valid_until)
+                .map(|quote| quote.id.clone());
+            if let Some(id) = closest_to_expiry {
+                self.quotes.remove(&id);
+            }
+        }
+        self.quotes.insert(quote.id.clone(), quote);
+    }
+
+    // * Returns a copy of the quote if it can still be used on the given side, expired quotes are dropped
+    fn valid_quote<E>(&mut self, quote_id: &str, side: LockSide) -> Result<Quote, QuoteLockError<E>> {
+        let quote = match self.quotes.get(quote_id) {
+            Some(quote) if quote.side == side => quote.clone(),
+            _ => {
+                return Err(QuoteLockError::UnknownQuote {
+                    quote_id: String::from(quote_id),
+                })
+            }
+        };
+        if quote.valid_until < self.time {
+            self.quotes.remove(quote_id);
+            return Err(QuoteLockError::ExpiredQuote {
+                quote_id: quote.id,
+                valid_until: quote.valid_until,
+            });
         }
+        Ok(quote)
+    }
 
-        let mut good_price = good_data.buy_exchange_rate * quantity;
+    // * Traders pay and get paid in eur, so eur is always exchanged 1:1 when it is not the base good
+    fn is_pinned_eur(&self, kind: GoodKind) -> bool {
+        kind == GoodKind::EUR && self.base != GoodKind::EUR
+    }
 
-        // * Apply the discount
-        if quantity >= FIRST_LOCK_BUY_DISCOUNT_LOWER_BOUND_QTY * available_good_qty {
-            if quantity < SECOND_LOCK_BUY_DISCOUNT_LOWER_BOUND_QTY * available_good_qty {
-                good_price *= FIRST_LOCK_BUY_DISCOUNT;
-            } else if quantity < THIRD_LOCK_BUY_DISCOUNT_LOWER_BOUND_QTY * available_good_qty {
-                good_price *= SECOND_LOCK_BUY_DISCOUNT;
-            } else if quantity < MAX_LOCK_BUY_DISCOUNT_LOWER_BOUND_QTY * available_good_qty {
-                good_price *= THIRD_LOCK_BUY_DISCOUNT;
-            } else {
-                good_price *= MAX_LOCK_BUY_DISCOUNT;
+    // * Volume tier and multiplier of an order of `quantity` units, eur excluded when it is pinned
+    fn volume(&self, side: LockSide, quantity: f32, ctx: &PricingContext) -> (Option<usize>, f32) {
+        if self.is_pinned_eur(ctx.kind) {
+            return (None, 1.0);
+        }
+        match side {
+            LockSide::Buy => (
+                self.pricing.buy_volume_tier(quantity, ctx),
+                self.pricing.buy_volume_multiplier(quantity, ctx),
+            ),
+            LockSide::Sell => (
+                self.pricing.sell_volume_tier(quantity, ctx),
+                self.pricing.sell_volume_multiplier(quantity, ctx),
+            ),
+        }
+    }
+
+    // * Buy price of an order before the volume tiers, the base good and pinned eur keep their exchange rate
+    fn base_buy_price(&self, quantity: f32, ctx: &PricingContext) -> f32 {
+        match self.quote_mode {
+            QuoteMode::Integrated if ctx.kind != self.base && !self.is_pinned_eur(ctx.kind) => {
+                integrated_buy_cost(|slice_ctx| self.decayed_buy_rate(slice_ctx), ctx, quantity)
             }
+            _ => self.good_data[&ctx.kind].buy_exchange_rate * quantity,
         }
+    }
 
-        Ok(good_price)
+    fn price_breakdown(
+        &self,
+        side: LockSide,
+        quantity: f32,
+        ctx: &PricingContext,
+        exchange_rate: f32,
+        base_price: f32,
+        price: f32,
+    ) -> PriceBreakdown {
+        let (volume_tier, volume_multiplier) = self.volume(side, quantity, ctx);
+        // * The base good and pinned eur are not priced against the mean
+        let fixed_price = ctx.kind == self.base || self.is_pinned_eur(ctx.kind);
+        let (position, price_tier) = if fixed_price {
+            (1.0, PriceTier::Default)
+        } else {
+            (ctx.value() / self.mean, self.decayed_price_tier(ctx))
+        };
+        PriceBreakdown {
+            side,
+            kind: ctx.kind,
+            quantity,
+            default_rate: ctx.default_price,
+            value: ctx.value(),
+            mean: self.mean,
+            position,
+            price_tier,
+            exchange_rate,
+            base_price,
+            volume_tier,
+            volume_multiplier,
+            price,
+        }
     }
 
-    fn get_sell_price(&self, kind: GoodKind, quantity: f32) -> Result<f32, MarketGetterError> {
-        if quantity.is_sign_negative() {
-            return Err(MarketGetterError::NonPositiveQuantityAsked);
+    // * A lock can still be redeemed on day `lock_time + MAX_LOCK_TIME + 1`, it is dropped by the time increment
+    // * that follows
+    fn remaining_lock_days(&self, lock_time: u64) -> u64 {
+        (lock_time + MAX_LOCK_TIME + 1).saturating_sub(self.time)
+    }
+
+    fn buy_lock_view(&self, token: &str, lock: &LockBuyGood) -> LockView {
+        LockView {
+            token: String::from(token),
+            side: LockSide::Buy,
+            kind: lock.locked_good.get_kind(),
+            quantity: lock.locked_good.get_qty(),
+            agreed_price: lock.buy_price,
+            lock_day: lock.lock_time,
+            remaining_days: self.remaining_lock_days(lock.lock_time),
         }
+    }
 
-        let eur_data = &self.good_data[&GoodKind::EUR];
+    fn sell_lock_view(&self, token: &str, lock: &LockSellGood) -> LockView {
+        LockView {
+            token: String::from(token),
+            side: LockSide::Sell,
+            kind: lock.locked_kind,
+            quantity: lock.receiving_good_qty,
+            agreed_price: lock.locked_base.get_qty() * self.default_price(self.base),
+            lock_day: lock.lock_time,
+            remaining_days: self.remaining_lock_days(lock.lock_time),
+        }
+    }
 
-        // * Getting the quantity availability
-        let available_eur_qty = eur_data.info.get_qty();
-        let quantity_cap = eur_data.initialization_qty * MINIMUM_EUR_QUANTITY_PERCENTAGE;
-        let price = self.good_data[&kind].sell_exchange_rate * quantity;
+    // * Fails if the lock belongs to someone else or if it has already been closed, other tokens are left to the redemption itself
+    fn check_buy_lock(
+        &mut self,
+        token: &String,
+        trader_name: &str,
+    ) -> Result<(), RedeemError<BuyError>> {
+        let owned_by_other = match self.buy_locks.get(token) {
+            Some(lock) => lock.trader_name != trader_name,
+            None => false,
+        };
 
-        if available_eur_qty - price < quantity_cap {
-            return Err(MarketGetterError::InsufficientGoodQuantityAvailable {
-                requested_good_kind: GoodKind::EUR,
-                requested_good_quantity: price,
-                available_good_quantity: available_eur_qty,
+        if owned_by_other {
+            self.write_on_log_file(log_format_buy!(NAME, token, Err()));
+            return Err(RedeemError::NotLockOwner {
+                token: token.clone(),
+                trader_name: String::from(trader_name),
             });
         }
 
-        if CHECK_SELL_PRICE {
-            eprintln!(
-                "Requested good {} with qty: {}, and exchange rate: {}",
-                kind, quantity, self.good_data[&kind].sell_exchange_rate
-            );
+        match self.token_registry.state(token) {
+            state @ (TokenState::Redeemed | TokenState::Cancelled) => {
+                self.write_on_log_file(log_format_buy!(NAME, token, Err()));
+                Err(RedeemError::Closed {
+                    token: token.clone(),
+                    state,
+                })
+            }
+            _ => Ok(()),
         }
-
-        Ok(price)
     }
 
-    fn get_goods(&self) -> Vec<GoodLabel> {
-        let mut prices: Vec<GoodLabel> = Vec::new();
-        for (kind, data) in &self.good_data {
-            prices.push(GoodLabel {
-                good_kind: *kind,
-                quantity: data.info.get_qty(),
-                exchange_rate_buy: data.buy_exchange_rate,
-                exchange_rate_sell: data.sell_exchange_rate,
+    // * Fails if the lock belongs to someone else or if it has already been closed, other tokens are left to the redemption itself
+    fn check_sell_lock(
+        &mut self,
+        token: &String,
+        trader_name: &str,
+    ) -> Result<(), RedeemError<SellError>> {
+        let owned_by_other = match self.sell_locks.get(token) {
+            Some(lock) => lock.trader_name != trader_name,
+            None => false,
+        };
+
+        if owned_by_other {
+            self.write_on_log_file(log_format_sell!(NAME, token, Err()));
+            return Err(RedeemError::NotLockOwner {
+                token: token.clone(),
+                trader_name: String::from(trader_name),
             });
         }
-        prices
+
+        match self.token_registry.state(token) {
+            state @ (TokenState::Redeemed | TokenState::Cancelled) => {
+                self.write_on_log_file(log_format_sell!(NAME, token, Err()));
+                Err(RedeemError::Closed {
+                    token: token.clone(),
+                    state,
+                })
+            }
+            _ => Ok(()),
+        }
     }
 
-    fn lock_buy(
+    // * Shared by `lock_buy` and `lock_buy_quote`: a quoted price replaces the current one, availability is still checked
+    fn lock_buy_at(
         &mut self,
         kind_to_buy: GoodKind,
         quantity_to_buy: f32,
         bid: f32,
         trader_name: String,
+        quoted_price: Option<f32>,
     ) -> Result<String, LockBuyError> {
         let token: String;
 
@@ -890,6 +1277,8 @@ impl Market for BVCMarket {
             }
         };
 
+        let good_price = quoted_price.unwrap_or(good_price);
+
         // * Non positive bid
         if bid <= 0.0 {
             self.write_on_log_file(log_format_lock_buy!(
@@ -932,7 +1321,7 @@ impl Market for BVCMarket {
         }
 
         // * Create a new buy transaction token
-        token = BVCMarket::token(String::from("lock_buy"), trader_name.clone(), self.time);
+        token = self.token("lock_buy");
 
         if self.oldest_lock_buy_time == Skip {
             self.oldest_lock_buy_time = Use(self.time, token.clone())
@@ -948,6 +1337,7 @@ impl Market for BVCMarket {
                     locked_good: good_splitted,
                     buy_price: bid,
                     lock_time: self.time,
+                    trader_name: trader_name.clone(),
                 },
             );
 
@@ -958,8 +1348,10 @@ impl Market for BVCMarket {
                 price: bid,
             });
 
+            self.touch_good(kind_to_buy);
+            self.record_activity();
             self.increment_time();
-            if kind_to_buy != GoodKind::EUR {
+            if kind_to_buy != self.base {
                 self.update_good_price(kind_to_buy);
             }
             self.write_on_log_file(log_format_lock_buy!(
@@ -976,94 +1368,14 @@ impl Market for BVCMarket {
         }
     }
 
-    fn buy(&mut self, token: String, cash: &mut Good) -> Result<Good, BuyError> {
-        if !self.buy_locks.contains_key(&token) {
-            // * Check if the good has expired
-            if self.expired_tokens.contains(&token) {
-                self.write_on_log_file(log_format_buy!(NAME, token, Err()));
-                return Err(BuyError::ExpiredToken {
-                    expired_token: token,
-                });
-            } else {
-                // * Otherwise it is an invalid token
-                self.write_on_log_file(log_format_buy!(NAME, token, Err()));
-                return Err(BuyError::UnrecognizedToken {
-                    unrecognized_token: token,
-                });
-            }
-        }
-
-        // * Invalid cash kind
-        if cash.get_kind() != GoodKind::EUR {
-            self.write_on_log_file(log_format_buy!(NAME, token, Err()));
-            return Err(BuyError::GoodKindNotDefault {
-                non_default_good_kind: cash.get_kind(),
-            });
-        }
-
-        // * Insufficient good quantity
-        if self.buy_locks[&token].buy_price > cash.get_qty() {
-            self.write_on_log_file(log_format_buy!(NAME, token, Err()));
-            return Err(BuyError::InsufficientGoodQuantity {
-                contained_quantity: cash.get_qty(),
-                pre_agreed_quantity: self.buy_locks[&token].buy_price,
-            });
-        }
-
-        // * Merge the eur good from the trader, notify other markets and return the locked good
-        let eur_to_pay = self.buy_locks[&token].buy_price;
-        let locked_good = self.buy_locks[&token].locked_good.clone(); // * There was a clone here
-        self.buy_locks.remove(&token);
-        
-        // * Updates the oldest lock if bought one was the oldest
-        match &self.oldest_lock_buy_time {
-            Use(_,tok) if *tok == token => {
-                let mut oldest_lock = Skip;
-                for (tk,good_lock) in &self.buy_locks{
-                    let cmp_time = good_lock.lock_time;
-                    match oldest_lock {
-                        Use(time,_) if cmp_time < time => oldest_lock = Use(cmp_time,tk.clone()),
-                        Skip => oldest_lock = Use(cmp_time,tk.clone()),
-                        _ => (),
-                    }
-                }
-                self.oldest_lock_buy_time = oldest_lock;
-            },
-            _ => (),
-        }
-
-        self.active_buy_locks -= 1;
-        if let Some(eur) = self.good_data.get_mut(&GoodKind::EUR) {
-            if SHOW_BUY_DETAILS {
-                eprintln!(
-                    "Adding {} euros to wallet eur {}",
-                    eur_to_pay,
-                    eur.info.get_qty()
-                );
-            }
-
-            eur.info.merge(cash.split(eur_to_pay).unwrap());
-            self.notify_markets(Event {
-                kind: EventKind::Bought,
-                good_kind: locked_good.get_kind(),
-                quantity: locked_good.get_qty(),
-                price: eur_to_pay,
-            });
-
-            self.increment_time();
-            self.write_on_log_file(log_format_buy!(NAME, token, Ok()));
-            Ok(locked_good)
-        } else {
-            panic!("Missing key: GoodKind::EUR in good_data ")
-        }
-    }
-
-    fn lock_sell(
+    // * Shared by `lock_sell` and `lock_sell_quote`: a quoted price replaces the current one, availability is still checked
+    fn lock_sell_at(
         &mut self,
         kind_to_sell: GoodKind,
         quantity_to_sell: f32,
         offer: f32,
         trader_name: String,
+        quoted_price: Option<f32>,
     ) -> Result<String, LockSellError> {
         let token: String;
 
@@ -1099,6 +1411,8 @@ impl Market for BVCMarket {
             }
         };
 
+        let good_price = quoted_price.unwrap_or(good_price);
+
         // * Non positive offer
         if offer <= 0.0 {
             self.write_on_log_file(log_format_lock_sell!(
@@ -1142,23 +1456,25 @@ impl Market for BVCMarket {
             });
         }
 
-        token = BVCMarket::token(String::from("lock_sell"), trader_name.clone(), self.time);
+        token = self.token("lock_sell");
 
         if self.oldest_lock_sell_time == Skip {
             self.oldest_lock_sell_time = Use(self.time, token.clone())
         }
 
-        //* Split the eur good, notify the markets and return the token
-        if let Some(tmp) = self.good_data.get_mut(&GoodKind::EUR) {
-            let eur_splitted = tmp.info.split(offer).unwrap();
+        //* Split the base good, notify the markets and return the token
+        let base_to_lock = self.eur_to_base(offer);
+        if let Some(tmp) = self.good_data.get_mut(&self.base) {
+            let base_splitted = tmp.info.split(base_to_lock).unwrap();
             self.active_sell_locks += 1;
             self.sell_locks.insert(
                 token.clone(),
                 LockSellGood {
-                    locked_eur: eur_splitted,
+                    locked_base: base_splitted,
                     receiving_good_qty: quantity_to_sell,
                     lock_time: self.time,
                     locked_kind: kind_to_sell,
+                    trader_name: trader_name.clone(),
                 },
             );
 
@@ -1169,8 +1485,10 @@ impl Market for BVCMarket {
                 price: offer,
             });
 
+            self.touch_good(kind_to_sell);
+            self.record_activity();
             self.increment_time();
-            if kind_to_sell != GoodKind::EUR {
+            if kind_to_sell != self.base {
                 self.update_good_price(kind_to_sell);
             }
             self.write_on_log_file(log_format_lock_sell!(
@@ -1183,14 +1501,119 @@ impl Market for BVCMarket {
             ));
             return Ok(token);
         } else {
-            panic!("Missing key: GoodKind::EUR in good_data ")
+            panic!("Missing key: {} in good_data ", self.base)
         }
     }
 
-    fn sell(&mut self, token: String, good: &mut Good) -> Result<Good, SellError> {
+    // * Shared by `buy` and `buy_partial`: when `allow_partial` is set the whole cash handed over is used as an instalment
+    fn redeem_buy(
+        &mut self,
+        token: String,
+        cash: &mut Good,
+        allow_partial: bool,
+    ) -> Result<Good, BuyError> {
+        if !self.buy_locks.contains_key(&token) {
+            // * Check if the good has expired
+            if self.token_registry.state(&token) == TokenState::Expired {
+                self.write_on_log_file(log_format_buy!(NAME, token, Err()));
+                return Err(BuyError::ExpiredToken {
+                    expired_token: token,
+                });
+            } else {
+                // * Otherwise it is an invalid token
+                self.write_on_log_file(log_format_buy!(NAME, token, Err()));
+                return Err(BuyError::UnrecognizedToken {
+                    unrecognized_token: token,
+                });
+            }
+        }
+
+        // * Invalid cash kind
+        if cash.get_kind() != GoodKind::EUR {
+            self.write_on_log_file(log_format_buy!(NAME, token, Err()));
+            return Err(BuyError::GoodKindNotDefault {
+                non_default_good_kind: cash.get_kind(),
+            });
+        }
+
+        // * The whole remaining price is taken when the cash covers it. An instalment never leaves less than the
+        // * tolerance still to pay, so that no dust remainder is left in the lock
+        let remaining_price = self.buy_locks[&token].buy_price;
+        let eur_to_pay = if allow_partial && cash.get_qty() < remaining_price {
+            f32::min(
+                cash.get_qty(),
+                remaining_price * (1.0 - PARTIAL_REDEEM_TOLERANCE),
+            )
+        } else {
+            remaining_price
+        };
+
+        // * Insufficient good quantity
+        if eur_to_pay > cash.get_qty() || eur_to_pay <= 0.0 {
+            self.write_on_log_file(log_format_buy!(NAME, token, Err()));
+            return Err(BuyError::InsufficientGoodQuantity {
+                contained_quantity: cash.get_qty(),
+                pre_agreed_quantity: remaining_price,
+            });
+        }
+
+        // * Hand over the whole lock when all the remaining price is paid, otherwise only its proportional share
+        let locked_good = if eur_to_pay >= remaining_price {
+            let lock = self.remove_buy_lock(&token);
+            self.token_registry
+                .retire(&token, TokenState::Redeemed, self.time);
+            lock.locked_good
+        } else {
+            let lock = self.buy_locks.get_mut(&token).unwrap();
+            let redeemed_qty = lock.locked_good.get_qty() * eur_to_pay / remaining_price;
+            lock.buy_price -= eur_to_pay;
+            lock.locked_good.split(redeemed_qty).unwrap()
+        };
+
+        // * Merge the eur good from the trader (as base good), notify other markets and return the locked good
+        let received = self.eur_to_base_good(cash.split(eur_to_pay).unwrap());
+        if let Some(base) = self.good_data.get_mut(&self.base) {
+            if SHOW_BUY_DETAILS {
+                eprintln!(
+                    "Adding {} euros to wallet {} {}",
+                    eur_to_pay,
+                    self.base,
+                    base.info.get_qty()
+                );
+            }
+
+            base.info.merge(received);
+            self.notify_markets(Event {
+                kind: EventKind::Bought,
+                good_kind: locked_good.get_kind(),
+                quantity: locked_good.get_qty(),
+                price: eur_to_pay,
+            });
+
+            let locked_kind = locked_good.get_kind();
+            self.touch_good(locked_kind);
+            self.record_activity();
+            self.increment_time();
+            if locked_kind != self.base {
+                self.update_good_price(locked_kind);
+            }
+            self.write_on_log_file(log_format_buy!(NAME, token, Ok()));
+            Ok(locked_good)
+        } else {
+            panic!("Missing key: {} in good_data ", self.base)
+        }
+    }
+
+    // * Shared by `sell` and `sell_partial`: when `allow_partial` is set the whole good handed over is used as an instalment
+    fn redeem_sell(
+        &mut self,
+        token: String,
+        good: &mut Good,
+        allow_partial: bool,
+    ) -> Result<Good, SellError> {
         if !self.sell_locks.contains_key(&token) {
             // * Check if the good has expired
-            if self.expired_tokens.contains(&token) {
+            if self.token_registry.state(&token) == TokenState::Expired {
                 self.write_on_log_file(log_format_sell!(NAME, token, Err()));
                 return Err(SellError::ExpiredToken {
                     expired_token: token,
@@ -1213,67 +1636,1340 @@ impl Market for BVCMarket {
             });
         }
 
+        // * The whole remaining quantity is taken when the good covers it. An instalment never leaves less than the
+        // * tolerance still to deliver, so that no dust remainder is left in the lock
+        let remaining_qty = self.sell_locks[&token].receiving_good_qty;
+        let qty_to_receive = if allow_partial && good.get_qty() < remaining_qty {
+            f32::min(
+                good.get_qty(),
+                remaining_qty * (1.0 - PARTIAL_REDEEM_TOLERANCE),
+            )
+        } else {
+            remaining_qty
+        };
+
         //* Quantity of goods not matching
-        if good.get_qty() < self.sell_locks[&token].receiving_good_qty {
+        if qty_to_receive > good.get_qty() || qty_to_receive <= 0.0 {
             self.write_on_log_file(log_format_sell!(NAME, token, Err()));
             return Err(SellError::InsufficientGoodQuantity {
                 contained_quantity: good.get_qty(),
-                pre_agreed_quantity: self.sell_locks[&token].receiving_good_qty,
+                pre_agreed_quantity: remaining_qty,
             });
         }
 
+        // * Hand over all the locked base good when all the remaining good is received, otherwise only its proportional share
+        let locked_kind = self.sell_locks[&token].locked_kind;
+        let locked_base = if qty_to_receive >= remaining_qty {
+            let lock = self.remove_sell_lock(&token);
+            self.token_registry
+                .retire(&token, TokenState::Redeemed, self.time);
+            lock.locked_base
+        } else {
+            let lock = self.sell_locks.get_mut(&token).unwrap();
+            let base_share = lock.locked_base.get_qty() * qty_to_receive / remaining_qty;
+            lock.receiving_good_qty -= qty_to_receive;
+            lock.locked_base.split(base_share).unwrap()
+        };
+        let locked_eur = self.base_to_eur(locked_base);
+
         // * Merge the good from the trader, notify other markets and return the locked eur pre agreed quantity
-        let lock_info = self.sell_locks[&token].clone();
-        self.sell_locks.remove(&token);
-
-        // * Updates the oldest lock if bought one was the oldest
-        match &self.oldest_lock_sell_time {
-            Use(_,tok) if *tok == token => {
-                let mut oldest_lock = Skip;
-                for (tk,good_lock) in &self.sell_locks{
-                    let cmp_time = good_lock.lock_time;
-                    match oldest_lock {
-                        Use(time,_) if cmp_time < time => oldest_lock = Use(cmp_time,tk.clone()),
-                        Skip => oldest_lock = Use(cmp_time,tk.clone()),
-                        _ => (),
-                    }
-                }
-                self.oldest_lock_sell_time = oldest_lock;
-            },
-            _ => (),
-        }
-
-        self.active_sell_locks -= 1;
-        if let Some(good_to_fill) = self.good_data.get_mut(&good.get_kind()) {
-            if SHOW_SELL_DETAILS {
-                eprintln!(
-                    "Adding {} {} to wallet {} {}",
-                    lock_info.receiving_good_qty,
-                    lock_info.locked_kind,
-                    lock_info.locked_kind,
-                    good_to_fill.info.get_qty()
-                );
-            }
+        if let Some(good_to_fill) = self.good_data.get_mut(&locked_kind) {
+            if SHOW_SELL_DETAILS {
+                eprintln!(
+                    "Adding {} {} to wallet {} {}",
+                    qty_to_receive,
+                    locked_kind,
+                    locked_kind,
+                    good_to_fill.info.get_qty()
+                );
+            }
 
             good_to_fill
                 .info
-                .merge(good.split(lock_info.receiving_good_qty).unwrap());
+                .merge(good.split(qty_to_receive).unwrap());
 
             self.notify_markets(Event {
                 kind: EventKind::Sold,
-                good_kind: good.get_kind(),
-                quantity: lock_info.receiving_good_qty,
-                price: lock_info.locked_eur.get_qty(),
+                good_kind: locked_kind,
+                quantity: qty_to_receive,
+                price: locked_eur.get_qty(),
             });
 
+            self.touch_good(locked_kind);
+            self.record_activity();
             self.increment_time();
-            if lock_info.locked_kind != GoodKind::EUR {
-                self.update_good_price(lock_info.locked_kind);
+            if locked_kind != self.base {
+                self.update_good_price(locked_kind);
             }
             self.write_on_log_file(log_format_sell!(NAME, token, Ok()));
-            Ok(lock_info.locked_eur)
+            Ok(locked_eur)
         } else {
-            panic!("Missing key: {} in good_data ", good.get_kind())
+            panic!("Missing key: {} in good_data ", locked_kind)
+        }
+    }
+}
+
+impl BVCMarket {
+    /// Same as `Market::new_random`, but returns the concrete market so that BVC specific methods stay reachable.
+    /// Wrap it with `Rc::new(RefCell::new(..))` to hand it out as an `Rc<RefCell<dyn Market>>`.
+    pub fn random() -> BVCMarket {
+        Self::random_with_config(BVCConfig::default())
+    }
+
+    /// Same as `random`, with custom settings.
+    pub fn random_with_config(config: BVCConfig) -> BVCMarket {
+        config.goods.validate(config.base_good);
+        let quantities = config
+            .goods
+            .random_allocation(config.allocation, STARTING_CAPITAL, config.base_good)
+            .into_iter()
+            .map(|(kind, value)| {
+                let default_rate = config.goods.default_exchange_rate(kind).unwrap();
+                (kind, value / default_rate)
+            })
+            .collect();
+        Self::with_goods(quantities, config)
+    }
+
+    /// Same as `random`, with the starting capital allocated according to `profile`.
+    pub fn random_with_profile(profile: AllocationProfile) -> BVCMarket {
+        Self::random_with_config(BVCConfig {
+            allocation: profile,
+            ..Default::default()
+        })
+    }
+
+    /// Same as `Market::new_with_quantities`, but returns the concrete market.
+    pub fn with_quantities(eur: f32, yen: f32, usd: f32, yuan: f32) -> BVCMarket {
+        Self::with_config(eur, yen, usd, yuan, BVCConfig::default())
+    }
+
+    /// Same as `with_quantities`, with custom settings.
+    /// The quantities of the goods missing from `config.goods` are ignored.
+    pub fn with_config(eur: f32, yen: f32, usd: f32, yuan: f32, config: BVCConfig) -> BVCMarket {
+        let quantities = [
+            (GoodKind::EUR, eur),
+            (GoodKind::YEN, yen),
+            (GoodKind::USD, usd),
+            (GoodKind::YUAN, yuan),
+        ]
+        .into_iter()
+        .collect();
+        Self::with_goods(quantities, config)
+    }
+
+    /// Market trading the goods of `config.goods`, with the given quantities (zero for the missing ones).
+    /// Panics if `config.base_good` is missing from `config.goods`, if a good appears twice in it, if a default
+    /// exchange rate is not positive and finite (or is not `1.0` for eur), if `config.rebalance_schedule` is `RebalanceSchedule::Every { days: 0 }`,
+    /// or if `config.rebalance_fee` is not in `[0, 1)`.
+    pub fn with_goods(quantities: HashMap<GoodKind, f32>, config: BVCConfig) -> BVCMarket {
+        config.goods.validate(config.base_good);
+        config.rebalance_schedule.validate();
+        // * A fee of 1 or more would destroy the converted goods, a negative one would create value
+        if !(0.0..1.0).contains(&config.rebalance_fee) {
+            panic!(
+                "The rebalance fee must be in [0, 1), found {} !",
+                config.rebalance_fee
+            );
+        }
+
+        let file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(format!("log_{}.txt", NAME))
+            .expect("Unable to create log file !");
+
+        let mut market: BVCMarket = BVCMarket {
+            time: 0,
+            oldest_lock_buy_time: Skip,
+            oldest_lock_sell_time: Skip,
+            active_buy_locks: 0,
+            active_sell_locks: 0,
+            mean_mode: config.mean_mode,
+            mean: 0.0,
+            good_data: HashMap::new(),
+            default_rates: HashMap::new(),
+            base: config.base_good,
+            buy_locks: HashMap::new(),
+            sell_locks: HashMap::new(),
+            subscribers: Vec::new(),
+            price_observers: Vec::new(),
+            log_file: file,
+            token_registry: TokenRegistry::new(
+                config.token_retention_days,
+                config.max_retired_tokens,
+            ),
+            pricing: config.pricing,
+            quote_mode: config.quote_mode,
+            activity: VecDeque::new(),
+            activity_window_days: config.activity_window_days,
+            idle_decay: config.idle_decay,
+            rebalance: config.rebalance,
+            rebalance_schedule: config.rebalance_schedule,
+            rebalance_fee: config.rebalance_fee,
+            rebalance_fees_paid: 0.0,
+            rebalance_history: VecDeque::new(),
+            competitor_rates: HashMap::new(),
+            quotes: HashMap::new(),
+            issued_tokens: 0,
+        };
+
+        for good in &config.goods.goods {
+            let quantity = quantities.get(&good.kind).copied().unwrap_or(0.0);
+            market.default_rates.insert(good.kind, good.default_exchange_rate);
+            market.good_data.insert(
+                good.kind,
+                GoodInfo {
+                    info: Good::new(good.kind, quantity),
+                    buy_exchange_rate: good.default_exchange_rate,
+                    sell_exchange_rate: good.default_exchange_rate,
+                    initialization_qty: quantity,
+                    kind_of_trade: Unknown,
+                    kind_of_trade_day: 0,
+                    last_trade_time: 0,
+                    idle_decay_progress: 0.0,
+                },
+            );
+        }
+
+        // * The mean is taken over the goods except the base one, whose price never changes
+        let (mut total, mut goods) = (0.0, 0.0);
+        for (kind, good_info) in &market.good_data {
+            if *kind != market.base {
+                total += good_info.initialization_qty * market.default_price(*kind);
+                goods += 1.0;
+            }
+        }
+        market.mean = if goods > 0.0 { total / goods } else { 0.0 };
+
+        if SHOW_MEAN {
+            eprintln!("initialization_mean : {}", market.mean);
+        }
+
+        let kinds: Vec<GoodKind> = market.good_data.keys().copied().collect();
+        for kind in kinds {
+            if kind != market.base {
+                market.update_good_price(kind);
+            }
+        }
+
+        let quantity = |kind: GoodKind| quantities.get(&kind).copied().unwrap_or(0.0);
+        market.write_on_log_file(log_format_market_init!(
+            NAME,
+            quantity(GoodKind::EUR),
+            quantity(GoodKind::USD),
+            quantity(GoodKind::YEN),
+            quantity(GoodKind::YUAN)
+        ));
+
+        market
+    }
+
+    /// Redeems a buy lock in instalments: all the EUR in `cash` (up to the remaining pre-agreed price) is taken,
+    /// and the matching share of the locked good is returned.
+    /// The remainder of the lock keeps its original lock time, so it still expires after `MAX_LOCK_TIME` days.
+    pub fn buy_partial(&mut self, token: String, cash: &mut Good) -> Result<Good, BuyError> {
+        self.redeem_buy(token, cash, true)
+    }
+
+    /// Redeems a sell lock in instalments: all of `good` (up to the remaining pre-agreed quantity) is taken,
+    /// and the matching share of the locked EUR is returned.
+    /// The remainder of the lock keeps its original lock time, so it still expires after `MAX_LOCK_TIME` days.
+    pub fn sell_partial(&mut self, token: String, good: &mut Good) -> Result<Good, SellError> {
+        self.redeem_sell(token, good, true)
+    }
+
+    /// Same as `buy`, but fails with `RedeemError::NotLockOwner` if the lock was opened by a trader other than `trader_name`,
+    /// and with `RedeemError::Closed` if the lock has already been redeemed or cancelled.
+    pub fn buy_as(
+        &mut self,
+        token: String,
+        cash: &mut Good,
+        trader_name: &str,
+    ) -> Result<Good, RedeemError<BuyError>> {
+        self.check_buy_lock(&token, trader_name)?;
+        self.redeem_buy(token, cash, false).map_err(RedeemError::Market)
+    }
+
+    /// Same as `buy_partial`, but fails with `RedeemError::NotLockOwner` if the lock was opened by a trader other than `trader_name`,
+    /// and with `RedeemError::Closed` if the lock has already been redeemed or cancelled.
+    pub fn buy_partial_as(
+        &mut self,
+        token: String,
+        cash: &mut Good,
+        trader_name: &str,
+    ) -> Result<Good, RedeemError<BuyError>> {
+        self.check_buy_lock(&token, trader_name)?;
+        self.redeem_buy(token, cash, true).map_err(RedeemError::Market)
+    }
+
+    /// Same as `sell`, but fails with `RedeemError::NotLockOwner` if the lock was opened by a trader other than `trader_name`,
+    /// and with `RedeemError::Closed` if the lock has already been redeemed or cancelled.
+    pub fn sell_as(
+        &mut self,
+        token: String,
+        good: &mut Good,
+        trader_name: &str,
+    ) -> Result<Good, RedeemError<SellError>> {
+        self.check_sell_lock(&token, trader_name)?;
+        self.redeem_sell(token, good, false).map_err(RedeemError::Market)
+    }
+
+    /// Same as `sell_partial`, but fails with `RedeemError::NotLockOwner` if the lock was opened by a trader other than `trader_name`,
+    /// and with `RedeemError::Closed` if the lock has already been redeemed or cancelled.
+    pub fn sell_partial_as(
+        &mut self,
+        token: String,
+        good: &mut Good,
+        trader_name: &str,
+    ) -> Result<Good, RedeemError<SellError>> {
+        self.check_sell_lock(&token, trader_name)?;
+        self.redeem_sell(token, good, true).map_err(RedeemError::Market)
+    }
+
+    /// Snapshots of the open buy locks of `trader_name`, the tokens of the other traders are never listed.
+    pub fn open_buy_locks(&self, trader_name: &str) -> Vec<LockView> {
+        self.buy_locks
+            .iter()
+            .filter(|(_, lock)| lock.trader_name == trader_name)
+            .map(|(token, lock)| self.buy_lock_view(token, lock))
+            .collect()
+    }
+
+    /// Snapshots of the open sell locks of `trader_name`, the tokens of the other traders are never listed.
+    pub fn open_sell_locks(&self, trader_name: &str) -> Vec<LockView> {
+        self.sell_locks
+            .iter()
+            .filter(|(_, lock)| lock.trader_name == trader_name)
+            .map(|(token, lock)| self.sell_lock_view(token, lock))
+            .collect()
+    }
+
+    /// Snapshot of the open lock with the given token, `None` if it is not open (see `token_state` for the reason).
+    pub fn lock_info(&self, token: &str) -> Option<LockView> {
+        self.buy_locks
+            .get(token)
+            .map(|lock| self.buy_lock_view(token, lock))
+            .or_else(|| {
+                self.sell_locks
+                    .get(token)
+                    .map(|lock| self.sell_lock_view(token, lock))
+            })
+    }
+
+    /// Precise state of a token: open, redeemed, expired, cancelled or unknown (never issued or already pruned).
+    pub fn token_state(&self, token: &str) -> TokenState {
+        self.token_registry.state(token)
+    }
+
+    /// Same as `token_state`, together with the market day in which the state was reached.
+    pub fn token_record(&self, token: &str) -> Option<TokenRecord> {
+        self.token_registry.record(token)
+    }
+
+    /// Cancels an open lock on behalf of its owner, giving the locked good (or eur) back to the market.
+    pub fn cancel_lock(&mut self, token: String, trader_name: &str) -> Result<(), CancelLockError> {
+        let owner = self
+            .buy_locks
+            .get(&token)
+            .map(|lock| lock.trader_name.clone())
+            .or_else(|| {
+                self.sell_locks
+                    .get(&token)
+                    .map(|lock| lock.trader_name.clone())
+            });
+
+        match owner {
+            None => Err(CancelLockError::NotOpen {
+                state: self.token_registry.state(&token),
+                token,
+            }),
+            Some(owner) if owner != trader_name => Err(CancelLockError::NotLockOwner {
+                token,
+                trader_name: String::from(trader_name),
+            }),
+            Some(_) => {
+                if self.buy_locks.contains_key(&token) {
+                    let kind = self.release_buy_lock(&token);
+                    if kind != self.base {
+                        self.update_good_price(kind);
+                    }
+                } else {
+                    self.release_sell_lock(&token);
+                }
+                self.token_registry
+                    .retire(&token, TokenState::Cancelled, self.time);
+                self.update_mean();
+                Ok(())
+            }
+        }
+    }
+
+    /// Registers an observer of the price changes that are not caused by trades, such as the re-prices after a rebalance.
+    pub fn add_price_observer(&mut self, observer: Box<dyn PriceObserver>) {
+        self.price_observers.push(observer);
+    }
+
+    /// Imported/exported status of every good, with the day it was set and the day it will be reset.
+    pub fn trade_statuses(&self) -> Vec<TradeStatus> {
+        self.good_data
+            .iter()
+            .map(|(kind, good_info)| {
+                let set_day = match good_info.kind_of_trade {
+                    Unknown => None,
+                    _ => Some(good_info.kind_of_trade_day),
+                };
+                TradeStatus {
+                    kind: *kind,
+                    kind_of_trade: good_info.kind_of_trade,
+                    set_day,
+                    resets_on: set_day.map(|day| day + DURATION_OF_CHOSEN_KIND_OF_TRADE),
+                }
+            })
+            .collect()
+    }
+
+    /// Conversions applied by the last rebalances, oldest first, up to 256 of them.
+    pub fn rebalance_history(&self) -> Vec<RebalanceRecord> {
+        self.rebalance_history.iter().copied().collect()
+    }
+
+    /// Eur value lost so far in the conversions of the rebalances, because of `BVCConfig::rebalance_fee`.
+    pub fn rebalance_fees_paid(&self) -> f32 {
+        self.rebalance_fees_paid
+    }
+
+    /// Quotes the price of buying `quantity` units of `kind`, guaranteed for 2 more days
+    /// if the quote is turned into a lock with `lock_buy_quote`.
+    pub fn quote_buy(&mut self, kind: GoodKind, quantity: f32) -> Result<Quote, MarketGetterError> {
+        let price = self.get_buy_price(kind, quantity)?;
+        let (volume_tier, _) = self.volume(LockSide::Buy, quantity, &self.pricing_context(kind));
+        let quote = Quote {
+            id: self.random_id("quote_buy"),
+            side: LockSide::Buy,
+            kind,
+            quantity,
+            price,
+            unit_rate: if quantity > 0.0 { price / quantity } else { 0.0 },
+            volume_tier,
+            valid_until: self.time + QUOTE_VALIDITY_DAYS,
+        };
+        self.store_quote(quote.clone());
+        Ok(quote)
+    }
+
+    /// Quotes the price paid for `quantity` units of `kind`, guaranteed for 2 more days
+    /// if the quote is turned into a lock with `lock_sell_quote`.
+    pub fn quote_sell(&mut self, kind: GoodKind, quantity: f32) -> Result<Quote, MarketGetterError> {
+        let price = self.get_sell_price(kind, quantity)?;
+        let (volume_tier, _) = self.volume(LockSide::Sell, quantity, &self.pricing_context(kind));
+        let quote = Quote {
+            id: self.random_id("quote_sell"),
+            side: LockSide::Sell,
+            kind,
+            quantity,
+            price,
+            unit_rate: if quantity > 0.0 { price / quantity } else { 0.0 },
+            volume_tier,
+            valid_until: self.time + QUOTE_VALIDITY_DAYS,
+        };
+        self.store_quote(quote.clone());
+        Ok(quote)
+    }
+
+    /// Explains how the price of buying `quantity` units of `kind` is computed: position of the good against the mean,
+    /// price tier, volume tier and final price.
+    pub fn explain_buy(
+        &self,
+        kind: GoodKind,
+        quantity: f32,
+    ) -> Result<PriceBreakdown, MarketGetterError> {
+        let price = self.get_buy_price(kind, quantity)?;
+        let ctx = self.pricing_context(kind);
+        let exchange_rate = self.good_data[&kind].buy_exchange_rate;
+        let base_price = self.base_buy_price(quantity, &ctx);
+        Ok(self.price_breakdown(
+            LockSide::Buy,
+            quantity,
+            &ctx,
+            exchange_rate,
+            base_price,
+            price,
+        ))
+    }
+
+    /// Explains how the price paid for `quantity` units of `kind` is computed, as `explain_buy` does for buys.
+    pub fn explain_sell(
+        &self,
+        kind: GoodKind,
+        quantity: f32,
+    ) -> Result<PriceBreakdown, MarketGetterError> {
+        let price = self.get_sell_price(kind, quantity)?;
+        let ctx = self.pricing_context(kind);
+        let exchange_rate = self.good_data[&kind].sell_exchange_rate;
+        Ok(self.price_breakdown(
+            LockSide::Sell,
+            quantity,
+            &ctx,
+            exchange_rate,
+            exchange_rate * quantity,
+            price,
+        ))
+    }
+
+    /// Opens a buy lock at the quoted price. The other lock constraints (available quantity, number of locks)
+    /// still apply, and a quote is consumed only by a successful lock.
+    pub fn lock_buy_quote(
+        &mut self,
+        quote_id: &str,
+        trader_name: String,
+    ) -> Result<String, QuoteLockError<LockBuyError>> {
+        let quote = self.valid_quote(quote_id, LockSide::Buy)?;
+        let token = self
+            .lock_buy_at(
+                quote.kind,
+                quote.quantity,
+                quote.price,
+                trader_name,
+                Some(quote.price),
+            )
+            .map_err(QuoteLockError::Market)?;
+        self.quotes.remove(quote_id);
+        Ok(token)
+    }
+
+    /// Opens a sell lock at the quoted price. The other lock constraints (available eur, number of locks)
+    /// still apply, and a quote is consumed only by a successful lock.
+    pub fn lock_sell_quote(
+        &mut self,
+        quote_id: &str,
+        trader_name: String,
+    ) -> Result<String, QuoteLockError<LockSellError>> {
+        let quote = self.valid_quote(quote_id, LockSide::Sell)?;
+        let token = self
+            .lock_sell_at(
+                quote.kind,
+                quote.quantity,
+                quote.price,
+                trader_name,
+                Some(quote.price),
+            )
+            .map_err(QuoteLockError::Market)?;
+        self.quotes.remove(quote_id);
+        Ok(token)
+    }
+}
+
+impl Notifiable for BVCMarket {
+    fn add_subscriber(&mut self, subscriber: Box<dyn Notifiable>) {
+        self.subscribers.push(subscriber);
+    }
+    fn on_event(&mut self, event: Event) {
+        match event.kind {
+            EventKind::Wait => (),
+            _ => {
+                // * Remember the buy exchange rate of the competitors, used by the idle price decay
+                let buy_side = matches!(event.kind, EventKind::Bought | EventKind::LockedBuy);
+                if buy_side && event.good_kind != GoodKind::EUR && event.quantity > 0.0 {
+                    self.competitor_rates
+                        .insert(event.good_kind, event.price / event.quantity);
+                }
+                self.record_activity()
+            }
+        }
+        self.increment_time();
+        /* match event.kind {
+            EventKind::Wait => self.increment_time(),
+            _ => (),
+        } */
+    }
+}
+
+impl Market for BVCMarket {
+    fn new_random() -> Rc<RefCell<dyn Market>>
+    where
+        Self: Sized,
+    {
+        Rc::new(RefCell::new(Self::random()))
+    }
+
+    fn new_with_quantities(eur: f32, yen: f32, usd: f32, yuan: f32) -> Rc<RefCell<dyn Market>>
+    where
+        Self: Sized,
+    {
+        Rc::new(RefCell::new(Self::with_quantities(eur, yen, usd, yuan)))
+    }
+
+    fn new_file(_path: &str) -> Rc<RefCell<dyn Market>>
+    where
+        Self: Sized,
+    {
+        Self::new_random()
+    }
+
+    fn get_name(&self) -> &'static str {
+        NAME
+    }
+
+    fn get_budget(&self) -> f32 {
+        self.good_data[&self.base].info.get_qty() * self.default_price(self.base)
+    }
+
+    fn get_buy_price(&self, kind: GoodKind, quantity: f32) -> Result<f32, MarketGetterError> {
+        if quantity.is_sign_negative() {
+            return Err(MarketGetterError::NonPositiveQuantityAsked);
+        }
+
+        // * Goods missing from the goods table are never available
+        if !self.good_data.contains_key(&kind) {
+            return Err(MarketGetterError::InsufficientGoodQuantityAvailable {
+                requested_good_kind: kind,
+                requested_good_quantity: quantity,
+                available_good_quantity: 0.0,
+            });
         }
+
+        let good_data = &self.good_data[&kind];
+
+        // * Getting the quantity availability
+        let available_good_qty = good_data.info.get_qty();
+        let quantity_cap = good_data.initialization_qty * MINIMUM_GOOD_QUANTITY_PERCENTAGE;
+
+        if available_good_qty - quantity < quantity_cap {
+            return Err(MarketGetterError::InsufficientGoodQuantityAvailable {
+                requested_good_kind: kind,
+                requested_good_quantity: quantity,
+                available_good_quantity: available_good_qty,
+            });
+        }
+
+        if CHECK_BUY_PRICE {
+            eprintln!(
+                "Requested good {} with qty: {}, and exchange rate: {}",
+                kind, quantity, good_data.buy_exchange_rate
+            );
+        }
+
+        let ctx = self.pricing_context(kind);
+        let good_price = self.base_buy_price(quantity, &ctx);
+
+        // * Apply the volume discount
+        let (_, volume_multiplier) = self.volume(LockSide::Buy, quantity, &ctx);
+        let good_price = good_price * volume_multiplier;
+
+        Ok(good_price)
+    }
+
+    fn get_sell_price(&self, kind: GoodKind, quantity: f32) -> Result<f32, MarketGetterError> {
+        if quantity.is_sign_negative() {
+            return Err(MarketGetterError::NonPositiveQuantityAsked);
+        }
+
+        // * Goods missing from the goods table are never accepted
+        if !self.good_data.contains_key(&kind) {
+            return Err(MarketGetterError::InsufficientGoodQuantityAvailable {
+                requested_good_kind: kind,
+                requested_good_quantity: quantity,
+                available_good_quantity: 0.0,
+            });
+        }
+
+        let base_data = &self.good_data[&self.base];
+
+        // * Getting the quantity availability, in eur
+        let base_rate = self.default_price(self.base);
+        let available_eur_qty = base_data.info.get_qty() * base_rate;
+        let quantity_cap =
+            base_data.initialization_qty * MINIMUM_EUR_QUANTITY_PERCENTAGE * base_rate;
+        let (_, volume_multiplier) =
+            self.volume(LockSide::Sell, quantity, &self.pricing_context(kind));
+        let price = self.good_data[&kind].sell_exchange_rate * quantity * volume_multiplier;
+
+        if available_eur_qty - price < quantity_cap {
+            return Err(MarketGetterError::InsufficientGoodQuantityAvailable {
+                requested_good_kind: self.base,
+                requested_good_quantity: price / base_rate,
+                available_good_quantity: base_data.info.get_qty(),
+            });
+        }
+
+        if CHECK_SELL_PRICE {
+            eprintln!(
+                "Requested good {} with qty: {}, and exchange rate: {}",
+                kind, quantity, self.good_data[&kind].sell_exchange_rate
+            );
+        }
+
+        Ok(price)
+    }
+
+    fn get_goods(&self) -> Vec<GoodLabel> {
+        let mut prices: Vec<GoodLabel> = Vec::new();
+        for (kind, data) in &self.good_data {
+            prices.push(GoodLabel {
+                good_kind: *kind,
+                quantity: data.info.get_qty(),
+                exchange_rate_buy: data.buy_exchange_rate,
+                exchange_rate_sell: data.sell_exchange_rate,
+            });
+        }
+        prices
+    }
+
+    fn lock_buy(
+        &mut self,
+        kind_to_buy: GoodKind,
+        quantity_to_buy: f32,
+        bid: f32,
+        trader_name: String,
+    ) -> Result<String, LockBuyError> {
+        self.lock_buy_at(kind_to_buy, quantity_to_buy, bid, trader_name, None)
+    }
+
+    fn buy(&mut self, token: String, cash: &mut Good) -> Result<Good, BuyError> {
+        self.redeem_buy(token, cash, false)
+    }
+
+    fn lock_sell(
+        &mut self,
+        kind_to_sell: GoodKind,
+        quantity_to_sell: f32,
+        offer: f32,
+        trader_name: String,
+    ) -> Result<String, LockSellError> {
+        self.lock_sell_at(kind_to_sell, quantity_to_sell, offer, trader_name, None)
+    }
+
+    fn sell(&mut self, token: String, good: &mut Good) -> Result<Good, SellError> {
+        self.redeem_sell(token, good, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // * A market holding the same eur value of every good, never rebalanced
+    fn balanced_market(config: BVCConfig) -> BVCMarket {
+        let eur = 10000.0;
+        BVCMarket::with_config(
+            eur,
+            eur * DEFAULT_EUR_YEN_EXCHANGE_RATE,
+            eur * DEFAULT_EUR_USD_EXCHANGE_RATE,
+            eur * DEFAULT_EUR_YUAN_EXCHANGE_RATE,
+            BVCConfig {
+                rebalance: Box::new(NoRebalance),
+                ..config
+            },
+        )
+    }
+
+    #[test]
+    fn same_day_locks_get_distinct_tokens() {
+        let mut market = balanced_market(BVCConfig::default());
+        let day = market.time;
+
+        let bid = market.get_buy_price(GoodKind::USD, 10.0).unwrap();
+        let first = market
+            .lock_buy(GoodKind::USD, 10.0, bid, String::from("trader"))
+            .unwrap();
+        // * Every lock moves the market forward by one day, go back to lock again on the same day
+        market.time = day;
+        let bid = market.get_buy_price(GoodKind::USD, 10.0).unwrap();
+        let second = market
+            .lock_buy(GoodKind::USD, 10.0, bid, String::from("trader"))
+            .unwrap();
+
+        assert_ne!(first, second);
+        assert_eq!(market.buy_locks[&first].lock_time, day);
+        assert_eq!(market.buy_locks[&second].lock_time, day);
+        assert_eq!(market.buy_locks.len(), 2);
+    }
+
+    #[test]
+    fn lock_is_redeemable_until_its_remaining_days_run_out() {
+        let mut market = balanced_market(BVCConfig::default());
+        let bid = market.get_buy_price(GoodKind::USD, 10.0).unwrap();
+        let token = market
+            .lock_buy(GoodKind::USD, 10.0, bid, String::from("trader"))
+            .unwrap();
+        let lock_day = market.buy_locks[&token].lock_time;
+
+        while market.time < lock_day + MAX_LOCK_TIME + 1 {
+            let remaining_days = market.lock_info(&token).unwrap().remaining_days;
+            assert_eq!(remaining_days, lock_day + MAX_LOCK_TIME + 1 - market.time);
+            market.increment_time();
+        }
+        // * Last valid day
+        assert_eq!(market.lock_info(&token).unwrap().remaining_days, 0);
+
+        market.increment_time();
+        assert!(market.lock_info(&token).is_none());
+        assert_eq!(market.token_state(&token), TokenState::Expired);
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() <= expected.abs() * 1e-4,
+            "{} is not close to {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn buy_partial_hands_over_a_proportional_share() {
+        let mut market = balanced_market(BVCConfig::default());
+        let bid = market.get_buy_price(GoodKind::USD, 100.0).unwrap();
+        let token = market
+            .lock_buy(GoodKind::USD, 100.0, bid, String::from("trader"))
+            .unwrap();
+
+        let mut cash = Good::new(GoodKind::EUR, bid * 0.25);
+        let bought = market.buy_partial(token.clone(), &mut cash).unwrap();
+        assert_close(bought.get_qty(), 25.0);
+        assert_eq!(cash.get_qty(), 0.0);
+        assert_close(market.buy_locks[&token].buy_price, bid * 0.75);
+        assert_close(market.buy_locks[&token].locked_good.get_qty(), 75.0);
+        assert_eq!(market.token_state(&token), TokenState::Issued);
+
+        // * Extra cash is left to the trader once the remaining price is paid
+        let mut cash = Good::new(GoodKind::EUR, bid);
+        let bought = market.buy_partial(token.clone(), &mut cash).unwrap();
+        assert_close(bought.get_qty(), 75.0);
+        assert_close(cash.get_qty(), bid * 0.25);
+        assert_eq!(market.token_state(&token), TokenState::Redeemed);
+    }
+
+    #[test]
+    fn sell_partial_hands_over_a_proportional_share() {
+        let mut market = balanced_market(BVCConfig::default());
+        let offer = market.get_sell_price(GoodKind::USD, 100.0).unwrap();
+        let token = market
+            .lock_sell(GoodKind::USD, 100.0, offer, String::from("trader"))
+            .unwrap();
+
+        let mut good = Good::new(GoodKind::USD, 40.0);
+        let paid = market.sell_partial(token.clone(), &mut good).unwrap();
+        assert_close(paid.get_qty(), offer * 0.4);
+        assert_eq!(good.get_qty(), 0.0);
+        assert_close(market.sell_locks[&token].receiving_good_qty, 60.0);
+        assert_eq!(market.token_state(&token), TokenState::Issued);
+
+        let mut good = Good::new(GoodKind::USD, 100.0);
+        let paid = market.sell_partial(token.clone(), &mut good).unwrap();
+        assert_close(paid.get_qty(), offer * 0.6);
+        assert_close(good.get_qty(), 40.0);
+        assert_eq!(market.token_state(&token), TokenState::Redeemed);
+    }
+
+    #[test]
+    fn instalments_never_leave_less_than_the_tolerance_due() {
+        let mut market = balanced_market(BVCConfig::default());
+        let trader = String::from("trader");
+        let bid = market.get_buy_price(GoodKind::USD, 100.0).unwrap();
+        let buy_token = market
+            .lock_buy(GoodKind::USD, 100.0, bid, trader.clone())
+            .unwrap();
+        let offer = market.get_sell_price(GoodKind::USD, 100.0).unwrap();
+        let sell_token = market
+            .lock_sell(GoodKind::USD, 100.0, offer, trader)
+            .unwrap();
+
+        // * Short of the whole amount by less than the tolerance
+        let almost = 1.0 - PARTIAL_REDEEM_TOLERANCE * 0.5;
+        let mut cash = Good::new(GoodKind::EUR, bid * almost);
+        market.buy_partial(buy_token.clone(), &mut cash).unwrap();
+        let remaining_price = market.buy_locks[&buy_token].buy_price;
+        // * The remainder is a difference of close numbers, compare it to the precision of the whole amount
+        assert!((remaining_price - bid * PARTIAL_REDEEM_TOLERANCE).abs() <= bid * 1e-6);
+        assert!(cash.get_qty() > 0.0);
+
+        let mut good = Good::new(GoodKind::USD, 100.0 * almost);
+        market.sell_partial(sell_token.clone(), &mut good).unwrap();
+        let remaining_qty = market.sell_locks[&sell_token].receiving_good_qty;
+        assert!((remaining_qty - 100.0 * PARTIAL_REDEEM_TOLERANCE).abs() <= 100.0 * 1e-6);
+        assert!(good.get_qty() > 0.0);
+    }
+
+    #[test]
+    fn partially_redeemed_locks_still_expire() {
+        let mut market = balanced_market(BVCConfig::default());
+        let trader = String::from("trader");
+        let bid = market.get_buy_price(GoodKind::USD, 100.0).unwrap();
+        let buy_token = market
+            .lock_buy(GoodKind::USD, 100.0, bid, trader.clone())
+            .unwrap();
+        let offer = market.get_sell_price(GoodKind::USD, 100.0).unwrap();
+        let sell_token = market
+            .lock_sell(GoodKind::USD, 100.0, offer, trader)
+            .unwrap();
+        let buy_lock_time = market.buy_locks[&buy_token].lock_time;
+        let sell_lock_time = market.sell_locks[&sell_token].lock_time;
+
+        let mut cash = Good::new(GoodKind::EUR, bid * 0.5);
+        market.buy_partial(buy_token.clone(), &mut cash).unwrap();
+        let mut good = Good::new(GoodKind::USD, 50.0);
+        market.sell_partial(sell_token.clone(), &mut good).unwrap();
+        assert_eq!(market.buy_locks[&buy_token].lock_time, buy_lock_time);
+        assert_eq!(market.sell_locks[&sell_token].lock_time, sell_lock_time);
+
+        while market.time <= sell_lock_time + MAX_LOCK_TIME + 1 {
+            market.increment_time();
+        }
+        let mut cash = Good::new(GoodKind::EUR, bid);
+        assert!(matches!(
+            market.buy_partial(buy_token.clone(), &mut cash),
+            Err(BuyError::ExpiredToken { expired_token }) if expired_token == buy_token
+        ));
+        let mut good = Good::new(GoodKind::USD, 100.0);
+        assert!(matches!(
+            market.sell_partial(sell_token.clone(), &mut good),
+            Err(SellError::ExpiredToken { expired_token }) if expired_token == sell_token
+        ));
+    }
+
+    #[test]
+    fn only_the_owner_can_redeem_or_cancel_a_lock() {
+        let mut market = balanced_market(BVCConfig::default());
+        let owner = String::from("owner");
+        let bid = market.get_buy_price(GoodKind::USD, 10.0).unwrap();
+        let buy_token = market
+            .lock_buy(GoodKind::USD, 10.0, bid, owner.clone())
+            .unwrap();
+        let offer = market.get_sell_price(GoodKind::USD, 10.0).unwrap();
+        let sell_token = market.lock_sell(GoodKind::USD, 10.0, offer, owner).unwrap();
+
+        let mut cash = Good::new(GoodKind::EUR, bid);
+        assert!(matches!(
+            market.buy_as(buy_token.clone(), &mut cash, "other"),
+            Err(RedeemError::NotLockOwner { .. })
+        ));
+        let mut good = Good::new(GoodKind::USD, 10.0);
+        assert!(matches!(
+            market.sell_as(sell_token.clone(), &mut good, "other"),
+            Err(RedeemError::NotLockOwner { .. })
+        ));
+        for token in [&buy_token, &sell_token] {
+            assert!(matches!(
+                market.cancel_lock(token.clone(), "other"),
+                Err(CancelLockError::NotLockOwner { .. })
+            ));
+        }
+
+        // * Nothing has been taken from the trader nor from the locks
+        assert_eq!(cash.get_qty(), bid);
+        assert_eq!(good.get_qty(), 10.0);
+        assert_eq!(market.buy_locks[&buy_token].locked_good.get_qty(), 10.0);
+        assert_eq!(market.sell_locks[&sell_token].receiving_good_qty, 10.0);
+        assert_eq!(market.token_state(&buy_token), TokenState::Issued);
+        assert_eq!(market.token_state(&sell_token), TokenState::Issued);
+    }
+
+    #[test]
+    fn closed_locks_are_reported_as_closed() {
+        let mut market = balanced_market(BVCConfig::default());
+        let owner = String::from("owner");
+        let bid = market.get_buy_price(GoodKind::USD, 10.0).unwrap();
+        let buy_token = market
+            .lock_buy(GoodKind::USD, 10.0, bid, owner.clone())
+            .unwrap();
+        let offer = market.get_sell_price(GoodKind::USD, 10.0).unwrap();
+        let sell_token = market
+            .lock_sell(GoodKind::USD, 10.0, offer, owner.clone())
+            .unwrap();
+
+        let mut cash = Good::new(GoodKind::EUR, bid * 2.0);
+        market.buy_as(buy_token.clone(), &mut cash, &owner).unwrap();
+        assert!(matches!(
+            market.buy_as(buy_token, &mut cash, &owner),
+            Err(RedeemError::Closed {
+                state: TokenState::Redeemed,
+                ..
+            })
+        ));
+
+        market.cancel_lock(sell_token.clone(), &owner).unwrap();
+        let mut good = Good::new(GoodKind::USD, 10.0);
+        assert!(matches!(
+            market.sell_as(sell_token, &mut good, &owner),
+            Err(RedeemError::Closed {
+                state: TokenState::Cancelled,
+                ..
+            })
+        ));
+    }
+
+    fn scarce_usd_market(config: BVCConfig) -> BVCMarket {
+        let eur = 10000.0;
+        BVCMarket::with_config(
+            eur,
+            eur * DEFAULT_EUR_YEN_EXCHANGE_RATE,
+            // * Scarce, so that its curve price is over the default one
+            eur * DEFAULT_EUR_USD_EXCHANGE_RATE * 0.5,
+            eur * DEFAULT_EUR_YUAN_EXCHANGE_RATE,
+            BVCConfig {
+                idle_decay: Some(IdleDecay {
+                    idle_days: 1,
+                    rate: 0.5,
+                    target: DecayTarget::DefaultExchangeRate,
+                }),
+                rebalance: Box::new(NoRebalance),
+                ..config
+            },
+        )
+    }
+
+    #[test]
+    fn idle_decay_survives_a_re_price() {
+        let mut market = scarce_usd_market(BVCConfig {
+            mean_mode: MeanMode::Live,
+            ..Default::default()
+        });
+        let curve_rate = market.good_data[&GoodKind::USD].buy_exchange_rate;
+        let default_rate = market.default_price(GoodKind::USD);
+        assert!(curve_rate > default_rate);
+
+        for _ in 0..5 {
+            market.increment_time();
+        }
+        let decayed_rate = market.good_data[&GoodKind::USD].buy_exchange_rate;
+        assert!(decayed_rate < curve_rate && decayed_rate > default_rate);
+
+        market.update_all_prices();
+        assert_eq!(
+            market.good_data[&GoodKind::USD].buy_exchange_rate,
+            decayed_rate
+        );
+    }
+
+    #[test]
+    fn idle_decay_applies_to_integrated_quotes_and_breakdowns() {
+        let mut market = scarce_usd_market(BVCConfig {
+            quote_mode: QuoteMode::Integrated,
+            ..Default::default()
+        });
+        for _ in 0..5 {
+            market.increment_time();
+        }
+
+        let quantity = 100.0;
+        let ctx = market.pricing_context(GoodKind::USD);
+        let curve_cost =
+            integrated_buy_cost(|c| market.pricing.buy_exchange_rate(c), &ctx, quantity);
+        let breakdown = market.explain_buy(GoodKind::USD, quantity).unwrap();
+        assert!(breakdown.base_price < curve_cost);
+
+        let decayed_rate = market.good_data[&GoodKind::USD].buy_exchange_rate;
+        let expected = decayed_rate / ctx.default_price - 1.0;
+        assert!(matches!(
+            breakdown.price_tier,
+            PriceTier::Inflation { premium } if (premium - expected).abs() <= expected * 1e-4
+        ));
+    }
+
+    #[test]
+    fn redeeming_a_buy_lock_clears_the_idle_decay() {
+        let mut market = scarce_usd_market(BVCConfig::default());
+        let trader = String::from("trader");
+        let bid = market.get_buy_price(GoodKind::USD, 10.0).unwrap();
+        let token = market.lock_buy(GoodKind::USD, 10.0, bid, trader).unwrap();
+        for _ in 0..3 {
+            market.increment_time();
+        }
+        assert!(market.good_data[&GoodKind::USD].idle_decay_progress > 0.0);
+
+        let mut cash = Good::new(GoodKind::EUR, bid);
+        market.buy(token, &mut cash).unwrap();
+        let ctx = market.pricing_context(GoodKind::USD);
+        assert_eq!(
+            market.good_data[&GoodKind::USD].buy_exchange_rate,
+            market.pricing.buy_exchange_rate(&ctx)
+        );
+    }
+
+    #[test]
+    fn live_mean_re_prices_the_other_goods() {
+        let mut initial = balanced_market(BVCConfig::default());
+        let mut live = balanced_market(BVCConfig {
+            mean_mode: MeanMode::Live,
+            ..Default::default()
+        });
+
+        // * Take 40% of the yen out of both markets
+        for market in [&mut initial, &mut live] {
+            let quantity = market.good_data[&GoodKind::YEN].info.get_qty() * 0.4;
+            let bid = market.get_buy_price(GoodKind::YEN, quantity).unwrap();
+            market
+                .lock_buy(GoodKind::YEN, quantity, bid, String::from("trader"))
+                .unwrap();
+        }
+
+        // * With the initial mean the usd is still at the mean, with the live one it is now over it
+        let default_rate = initial.default_price(GoodKind::USD);
+        let initial_rate = initial.good_data[&GoodKind::USD].buy_exchange_rate;
+        let live_rate = live.good_data[&GoodKind::USD].buy_exchange_rate;
+        assert!(live.mean < initial.mean);
+        assert!((initial_rate / default_rate - 1.0).abs() < 1e-4);
+        assert!((live_rate / default_rate - SECOND_DEFLATION_PRICE_DISCOUNT).abs() < 1e-4);
+    }
+
+    #[test]
+    fn live_mean_of_a_market_without_other_goods_is_zero() {
+        let mut market = balanced_market(BVCConfig {
+            mean_mode: MeanMode::Live,
+            goods: GoodsTable {
+                goods: vec![GoodSpec {
+                    kind: GoodKind::EUR,
+                    default_exchange_rate: 1.0,
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        market.increment_time();
+        assert_eq!(market.mean, 0.0);
+    }
+
+    // * Every good close to (or at) its reserve floor, with an open buy lock on the yen and an open sell lock
+    // * on the usd. Every good starts from 10000 eur of value, so the floors are 2500 eur (2000 eur for eur).
+    fn market_near_floors() -> (BVCMarket, String, String) {
+        let mut market = balanced_market(BVCConfig::default());
+        let trader = String::from("trader");
+
+        let yen = market.good_data[&GoodKind::YEN].info.get_qty() * 0.5;
+        let bid = market.get_buy_price(GoodKind::YEN, yen).unwrap();
+        let buy_token = market
+            .lock_buy(GoodKind::YEN, yen, bid, trader.clone())
+            .unwrap();
+        let usd = 1000.0 * DEFAULT_EUR_USD_EXCHANGE_RATE;
+        let offer = market.get_sell_price(GoodKind::USD, usd).unwrap();
+        let sell_token = market.lock_sell(GoodKind::USD, usd, offer, trader).unwrap();
+
+        let values = [
+            (GoodKind::EUR, 2100.0),
+            (GoodKind::YEN, 2700.0),
+            (GoodKind::USD, 2500.0),
+            (GoodKind::YUAN, 2500.0),
+        ];
+        for (kind, value) in values {
+            let default_price = market.default_price(kind);
+            let info = &mut market.good_data.get_mut(&kind).unwrap().info;
+            let excess = info.get_qty() - value / default_price;
+            info.split(excess).unwrap();
+        }
+        (market, buy_token, sell_token)
+    }
+
+    fn assert_above_floors(market: &BVCMarket) {
+        for (kind, good_info) in &market.good_data {
+            let floor = market.reserve_floor(*kind);
+            assert!(
+                good_info.info.get_qty() >= floor * (1.0 - 1e-5),
+                "{} went below its floor: {} < {}",
+                kind,
+                good_info.info.get_qty(),
+                floor
+            );
+        }
+    }
+
+    #[test]
+    fn rebalance_strategies_never_breach_the_floors() {
+        let strategies: Vec<Box<dyn RebalanceStrategy>> = vec![
+            Box::new(MeanBalancing),
+            Box::new(TargetWeights {
+                weights: [(GoodKind::YEN, 0.0), (GoodKind::YUAN, 1.0)]
+                    .into_iter()
+                    .collect(),
+            }),
+            Box::new(EurReserve { share: 0.0 }),
+        ];
+
+        for strategy in strategies {
+            let (mut market, buy_token, sell_token) = market_near_floors();
+            let locked_yen = market.buy_locks[&buy_token].locked_good.get_qty();
+            let locked_base = market.sell_locks[&sell_token].locked_base.get_qty();
+
+            // * Every strategy wants more than what the goods hold over their floors
+            let transfers = strategy.plan(&market.rebalance_goods());
+            assert!(!transfers.is_empty());
+            for transfer in transfers {
+                market.apply_transfer(transfer);
+            }
+
+            assert_above_floors(&market);
+            assert_eq!(
+                market.buy_locks[&buy_token].locked_good.get_qty(),
+                locked_yen
+            );
+            assert_eq!(
+                market.sell_locks[&sell_token].locked_base.get_qty(),
+                locked_base
+            );
+        }
+    }
+
+    #[test]
+    fn oversized_transfers_stop_at_the_floors() {
+        let (mut market, _, _) = market_near_floors();
+        let transfers = [
+            (GoodKind::YEN, GoodKind::YUAN),
+            (GoodKind::EUR, GoodKind::USD),
+        ];
+        for (from, to) in transfers {
+            assert!(market.apply_transfer(Transfer {
+                from,
+                to,
+                eur_value: STARTING_CAPITAL,
+            }));
+        }
+        assert_above_floors(&market);
+    }
+
+    fn kind_of_trade(market: &BVCMarket, kind: GoodKind) -> KindOfTrade {
+        market.good_data[&kind].kind_of_trade
+    }
+
+    fn mark_usd_as_imported(market: &mut BVCMarket) {
+        assert!(market.apply_transfer(Transfer {
+            from: GoodKind::YUAN,
+            to: GoodKind::USD,
+            eur_value: 100.0,
+        }));
+    }
+
+    #[test]
+    fn kind_of_trade_resets_after_24_days() {
+        // * Never rebalanced, so only the test marks the goods
+        let mut market = balanced_market(BVCConfig::default());
+        mark_usd_as_imported(&mut market);
+        let set_day = market.time;
+
+        let status = market
+            .trade_statuses()
+            .into_iter()
+            .find(|status| status.kind == GoodKind::USD)
+            .unwrap();
+        assert_eq!(status.set_day, Some(set_day));
+        assert_eq!(
+            status.resets_on,
+            Some(set_day + DURATION_OF_CHOSEN_KIND_OF_TRADE)
+        );
+
+        for _ in 0..DURATION_OF_CHOSEN_KIND_OF_TRADE - 1 {
+            market.increment_time();
+            assert_eq!(kind_of_trade(&market, GoodKind::USD), Imported);
+            assert_eq!(kind_of_trade(&market, GoodKind::YUAN), Exported);
+        }
+
+        market.increment_time();
+        assert_eq!(market.time, set_day + DURATION_OF_CHOSEN_KIND_OF_TRADE);
+        assert_eq!(kind_of_trade(&market, GoodKind::USD), Unknown);
+        assert_eq!(kind_of_trade(&market, GoodKind::YUAN), Unknown);
+    }
+
+    #[test]
+    fn a_new_mark_restarts_the_24_days() {
+        let mut market = balanced_market(BVCConfig::default());
+        mark_usd_as_imported(&mut market);
+        for _ in 0..10 {
+            market.increment_time();
+        }
+        mark_usd_as_imported(&mut market);
+        let set_day = market.time;
+
+        while market.time < set_day + DURATION_OF_CHOSEN_KIND_OF_TRADE - 1 {
+            market.increment_time();
+            assert_eq!(kind_of_trade(&market, GoodKind::USD), Imported);
+        }
+
+        market.increment_time();
+        assert_eq!(kind_of_trade(&market, GoodKind::USD), Unknown);
+    }
+
+    #[test]
+    #[should_panic(expected = "missing from the goods table")]
+    fn base_good_missing_from_the_goods_table_is_rejected() {
+        balanced_market(BVCConfig {
+            base_good: GoodKind::YUAN,
+            goods: GoodsTable {
+                goods: vec![GoodSpec {
+                    kind: GoodKind::EUR,
+                    default_exchange_rate: 1.0,
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "default exchange rate of EUR must be 1.0")]
+    fn eur_with_another_default_exchange_rate_is_rejected() {
+        let mut goods = GoodsTable::default();
+        for good in goods.goods.iter_mut() {
+            if good.kind == GoodKind::EUR {
+                good.default_exchange_rate = 1.1;
+            }
+        }
+        balanced_market(BVCConfig {
+            goods,
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "rebalance fee must be in [0, 1)")]
+    fn whole_rebalance_fee_is_rejected() {
+        balanced_market(BVCConfig {
+            rebalance_fee: 1.0,
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "rebalance fee must be in [0, 1)")]
+    fn negative_rebalance_fee_is_rejected() {
+        balanced_market(BVCConfig {
+            rebalance_fee: -0.1,
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "rebalance fee must be in [0, 1)")]
+    fn nan_rebalance_fee_is_rejected() {
+        balanced_market(BVCConfig {
+            rebalance_fee: f32::NAN,
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn eur_is_exchanged_one_to_one_when_it_is_not_the_base_good() {
+        let mut market = balanced_market(BVCConfig {
+            base_good: GoodKind::USD,
+            ..Default::default()
+        });
+
+        // * Big enough for the volume tiers, and to leave eur scarce
+        let quantity = market.good_data[&GoodKind::EUR].info.get_qty() * 0.5;
+        assert_eq!(
+            market.get_buy_price(GoodKind::EUR, quantity).unwrap(),
+            quantity
+        );
+        let token = market
+            .lock_buy(GoodKind::EUR, quantity, quantity, String::from("trader"))
+            .unwrap();
+        let mut cash = Good::new(GoodKind::EUR, quantity);
+        let bought = market.buy(token, &mut cash).unwrap();
+        assert_eq!(bought.get_qty(), quantity);
+
+        let eur_info = &market.good_data[&GoodKind::EUR];
+        assert_eq!(eur_info.buy_exchange_rate, 1.0);
+        assert_eq!(eur_info.sell_exchange_rate, 1.0);
+        assert_eq!(market.get_sell_price(GoodKind::EUR, 100.0).unwrap(), 100.0);
     }
 }
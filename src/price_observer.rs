@@ -0,0 +1,19 @@
+use unitn_market_2022::good::good_kind::GoodKind;
+
+/// Change of the exchange rates of a good that was not caused by a trade of that good
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceChange {
+    pub kind: GoodKind,
+    pub old_buy_exchange_rate: f32,
+    pub new_buy_exchange_rate: f32,
+    pub old_sell_exchange_rate: f32,
+    pub new_sell_exchange_rate: f32,
+    /// Market day in which the change happened
+    pub day: u64,
+}
+
+/// Receives the price changes of a market, registered with `add_price_observer`.
+/// The events of the `Notifiable` trait only cover trades, so re-prices are reported here.
+pub trait PriceObserver {
+    fn on_price_change(&mut self, change: PriceChange);
+}
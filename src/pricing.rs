@@ -0,0 +1,592 @@
+use unitn_market_2022::good::good_kind::GoodKind;
+
+use crate::{
+    BUY_TO_SELL_PERCENTAGE, DEFAULT_PRICE_LOWER_BOUND_QTY_PERCENTAGE,
+    FIRST_DEFLATION_PRICE_DISCOUNT, FIRST_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE,
+    FIRST_LOCK_BUY_DISCOUNT, FIRST_LOCK_BUY_DISCOUNT_LOWER_BOUND_QTY, FIRST_LOCK_SELL_DISCOUNT,
+    FIRST_LOCK_SELL_DISCOUNT_LOWER_BOUND_QTY, MAX_DEFLATION_PRICE_DISCOUNT,
+    MAX_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE, MAX_INFLATION_PRICE_INCREASE_PERCENTAGE,
+    MAX_LOCK_BUY_DISCOUNT, MAX_LOCK_BUY_DISCOUNT_LOWER_BOUND_QTY, MAX_LOCK_SELL_DISCOUNT,
+    MAX_LOCK_SELL_DISCOUNT_LOWER_BOUND_QTY, MINIMUM_GOOD_QUANTITY_PERCENTAGE,
+    SECOND_DEFLATION_PRICE_DISCOUNT, SECOND_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE,
+    SECOND_LOCK_BUY_DISCOUNT, SECOND_LOCK_BUY_DISCOUNT_LOWER_BOUND_QTY,
+    THIRD_DEFLATION_PRICE_DISCOUNT, THIRD_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE,
+    THIRD_LOCK_BUY_DISCOUNT, THIRD_LOCK_BUY_DISCOUNT_LOWER_BOUND_QTY,
+};
+
+//Anchors of the smooth deflation curves: (good quantity over mean, price discount), matching the documented tiers
+const DEFLATION_ANCHORS: [(f32, f32); 5] = [
+    (DEFAULT_PRICE_LOWER_BOUND_QTY_PERCENTAGE, 1.0),
+    (
+        FIRST_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE,
+        FIRST_DEFLATION_PRICE_DISCOUNT,
+    ),
+    (
+        SECOND_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE,
+        SECOND_DEFLATION_PRICE_DISCOUNT,
+    ),
+    (
+        THIRD_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE,
+        THIRD_DEFLATION_PRICE_DISCOUNT,
+    ),
+    (
+        MAX_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE,
+        MAX_DEFLATION_PRICE_DISCOUNT,
+    ),
+];
+
+//Inventory aware sell spread default constants
+const DEFAULT_INVENTORY_SELL_MAX_EXCESS_DISCOUNT: f32 = 0.03;
+const DEFAULT_INVENTORY_SELL_MAX_SHORTAGE_PREMIUM: f32 = 0.05;
+const MAX_SELL_TO_BUY_RATIO: f32 = 0.999;
+
+//Activity driven sell spread default constants
+const DEFAULT_VOLATILITY_SPREAD_FLOOR: f32 = 0.005;
+const DEFAULT_VOLATILITY_SPREAD_PER_TRADE: f32 = 0.001;
+const DEFAULT_VOLATILITY_SPREAD_MAX: f32 = 0.03;
+
+//Slices used to integrate the price curve in the integrated quote mode
+const QUOTE_INTEGRATION_STEPS: u32 = 32;
+
+//Logarithmic market maker default constants
+const DEFAULT_LOGARITHMIC_LIQUIDITY: f32 = 0.05;
+const DEFAULT_LOGARITHMIC_MIN_PRICE_RATIO: f32 = 0.9;
+const DEFAULT_LOGARITHMIC_MAX_PRICE_RATIO: f32 = 1.1;
+
+/// What the market knows about a good when pricing it
+#[derive(Debug, Clone, Copy)]
+pub struct PricingContext {
+    pub kind: GoodKind,
+    /// Default exchange rate of the good (eur for one unit)
+    pub default_price: f32,
+    /// Quantity currently held by the market
+    pub quantity: f32,
+    /// Quantity the market has been initialized with
+    pub initialization_qty: f32,
+    /// Mean eur value of the goods except the base one (eur by default), see the crate docs
+    pub mean: f32,
+    /// Trades and events of other markets seen in the recent activity window
+    pub recent_activity: usize,
+}
+
+impl PricingContext {
+    /// Eur value of the held quantity, at the default exchange rate
+    pub fn value(&self) -> f32 {
+        self.quantity * self.default_price
+    }
+
+    /// Eur value of the initialization quantity, at the default exchange rate
+    pub fn initial_value(&self) -> f32 {
+        self.initialization_qty * self.default_price
+    }
+}
+
+/// Pricing policy of the market, `BVCMarket` delegates every price computation to it
+pub trait PricingStrategy {
+    /// Buy exchange rate of the good (eur the trader pays for one unit)
+    fn buy_exchange_rate(&self, ctx: &PricingContext) -> f32;
+
+    /// Sell exchange rate of the good (eur the market pays for one unit), given its buy exchange rate
+    fn sell_exchange_rate(&self, buy_exchange_rate: f32, _ctx: &PricingContext) -> f32 {
+        buy_exchange_rate * BUY_TO_SELL_PERCENTAGE
+    }
+
+    /// Multiplier applied to `buy_exchange_rate * quantity` when a trader asks for `quantity` units
+    fn buy_volume_multiplier(&self, _quantity: f32, _ctx: &PricingContext) -> f32 {
+        1.0
+    }
+
+    /// Multiplier applied to `sell_exchange_rate * quantity` when a trader offers `quantity` units
+    fn sell_volume_multiplier(&self, _quantity: f32, _ctx: &PricingContext) -> f32 {
+        1.0
+    }
+
+    /// Which region of the price curve the good is in, used to explain prices to traders
+    fn price_tier(&self, _ctx: &PricingContext) -> PriceTier {
+        PriceTier::Unclassified
+    }
+
+    /// Volume tier (counted from 1) reached when a trader asks for `quantity` units, if the strategy has tiers
+    fn buy_volume_tier(&self, _quantity: f32, _ctx: &PricingContext) -> Option<usize> {
+        None
+    }
+
+    /// Volume tier (counted from 1) reached when a trader offers `quantity` units, if the strategy has tiers
+    fn sell_volume_tier(&self, _quantity: f32, _ctx: &PricingContext) -> Option<usize> {
+        None
+    }
+}
+
+/// Region of the price curve a good is in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PriceTier {
+    /// Below the mean, the buy exchange rate is `premium` over the default one
+    Inflation { premium: f32 },
+    /// Between the mean and the first deflation threshold, the default exchange rate applies
+    Default,
+    /// Over the mean, in the given deflation tier (counted from 1), the default exchange rate is multiplied by `discount`
+    Deflation { tier: usize, discount: f32 },
+    /// Over the mean on a smooth deflation curve, the default exchange rate is multiplied by `discount`
+    Curve { discount: f32 },
+    /// The strategy does not classify its prices
+    Unclassified,
+}
+
+/// A volume tier: orders of at least `lower_bound_qty` times the quantity held by the market get their price
+/// multiplied by `multiplier`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolumeTier {
+    pub lower_bound_qty: f32,
+    pub multiplier: f32,
+}
+
+impl VolumeTier {
+    /// The documented `lock buy` discounts
+    pub fn default_buy_tiers() -> Vec<VolumeTier> {
+        vec![
+            VolumeTier {
+                lower_bound_qty: FIRST_LOCK_BUY_DISCOUNT_LOWER_BOUND_QTY,
+                multiplier: FIRST_LOCK_BUY_DISCOUNT,
+            },
+            VolumeTier {
+                lower_bound_qty: SECOND_LOCK_BUY_DISCOUNT_LOWER_BOUND_QTY,
+                multiplier: SECOND_LOCK_BUY_DISCOUNT,
+            },
+            VolumeTier {
+                lower_bound_qty: THIRD_LOCK_BUY_DISCOUNT_LOWER_BOUND_QTY,
+                multiplier: THIRD_LOCK_BUY_DISCOUNT,
+            },
+            VolumeTier {
+                lower_bound_qty: MAX_LOCK_BUY_DISCOUNT_LOWER_BOUND_QTY,
+                multiplier: MAX_LOCK_BUY_DISCOUNT,
+            },
+        ]
+    }
+
+    /// The documented `lock sell` volume tiers, not applied unless set in `TieredPricing::sell_volume_tiers`
+    pub fn default_sell_tiers() -> Vec<VolumeTier> {
+        vec![
+            VolumeTier {
+                lower_bound_qty: FIRST_LOCK_SELL_DISCOUNT_LOWER_BOUND_QTY,
+                multiplier: FIRST_LOCK_SELL_DISCOUNT,
+            },
+            VolumeTier {
+                lower_bound_qty: MAX_LOCK_SELL_DISCOUNT_LOWER_BOUND_QTY,
+                multiplier: MAX_LOCK_SELL_DISCOUNT,
+            },
+        ]
+    }
+}
+
+// * Highest tier reached by the order (counted from 1), tiers are sorted by increasing `lower_bound_qty`
+fn volume_tier(tiers: &[VolumeTier], quantity: f32, available_good_qty: f32) -> Option<usize> {
+    let mut reached = None;
+    for (index, tier) in tiers.iter().enumerate() {
+        if quantity >= tier.lower_bound_qty * available_good_qty {
+            reached = Some(index + 1);
+        }
+    }
+    reached
+}
+
+fn volume_tier_multiplier(tiers: &[VolumeTier], quantity: f32, available_good_qty: f32) -> f32 {
+    match volume_tier(tiers, quantity, available_good_qty) {
+        Some(tier) => tiers[tier - 1].multiplier,
+        None => 1.0,
+    }
+}
+
+/// How the market turns an exchange rate into the price of a whole order
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum QuoteMode {
+    /// Current buy exchange rate times the quantity, as documented
+    #[default]
+    Flat,
+    /// Buy exchange rate integrated over the quantity removed from the market, so that big orders pay
+    /// for how far they push the market inventory along the price curve
+    Integrated,
+}
+
+// * Midpoint rule integration of the buy exchange rate while the market quantity goes from
+// * `ctx.quantity` down to `ctx.quantity - quantity`
+pub(crate) fn integrated_buy_cost(
+    buy_exchange_rate: impl Fn(&PricingContext) -> f32,
+    ctx: &PricingContext,
+    quantity: f32,
+) -> f32 {
+    let slice = quantity / QUOTE_INTEGRATION_STEPS as f32;
+    let mut cost = 0.0;
+    for step in 0..QUOTE_INTEGRATION_STEPS {
+        let slice_ctx = PricingContext {
+            quantity: ctx.quantity - slice * (step as f32 + 0.5),
+            ..*ctx
+        };
+        cost += buy_exchange_rate(&slice_ctx) * slice;
+    }
+    cost
+}
+
+/// How the sell exchange rate of a good is derived
+#[derive(Debug, Clone, Copy)]
+pub enum SellSpread {
+    /// Buy exchange rate times the given ratio, `Fixed(BUY_TO_SELL_PERCENTAGE)` is the documented behaviour
+    Fixed(f32),
+    /// Driven by the market inventory of the good, independently of its buy exchange rate:
+    /// `base` times the default price, lowered by up to `max_excess_discount` as the good grows over the mean
+    /// (the whole discount is reached at the highest deflation threshold), and raised by up to
+    /// `max_shortage_premium` as the good falls from the mean to its `MINIMUM_GOOD_QUANTITY_PERCENTAGE` cushion
+    InventoryAware {
+        base: f32,
+        max_excess_discount: f32,
+        max_shortage_premium: f32,
+    },
+    /// Driven by the recent activity: the spread below the buy exchange rate is `floor` in quiet periods,
+    /// and widens by `per_trade` for every trade seen in the activity window, up to `max`
+    Volatility {
+        floor: f32,
+        per_trade: f32,
+        max: f32,
+    },
+    /// Any other function of the buy exchange rate and of the good
+    Custom(fn(f32, &PricingContext) -> f32),
+}
+
+impl SellSpread {
+    /// `InventoryAware` with the documented parameters
+    pub fn inventory_aware() -> SellSpread {
+        SellSpread::InventoryAware {
+            base: BUY_TO_SELL_PERCENTAGE,
+            max_excess_discount: DEFAULT_INVENTORY_SELL_MAX_EXCESS_DISCOUNT,
+            max_shortage_premium: DEFAULT_INVENTORY_SELL_MAX_SHORTAGE_PREMIUM,
+        }
+    }
+
+    /// `Volatility` with the documented parameters
+    pub fn volatility() -> SellSpread {
+        SellSpread::Volatility {
+            floor: DEFAULT_VOLATILITY_SPREAD_FLOOR,
+            per_trade: DEFAULT_VOLATILITY_SPREAD_PER_TRADE,
+            max: DEFAULT_VOLATILITY_SPREAD_MAX,
+        }
+    }
+
+    /// Sell exchange rate of the good, never above `MAX_SELL_TO_BUY_RATIO` times its buy exchange rate
+    /// so that buying and selling back is never profitable
+    pub fn sell_exchange_rate(&self, buy_exchange_rate: f32, ctx: &PricingContext) -> f32 {
+        let sell_exchange_rate = match *self {
+            SellSpread::Fixed(ratio) => buy_exchange_rate * ratio,
+            SellSpread::InventoryAware {
+                base,
+                max_excess_discount,
+                max_shortage_premium,
+            } => {
+                let (good_qty, mean) = (ctx.value(), ctx.mean);
+                let cushion = ctx.initial_value() * MINIMUM_GOOD_QUANTITY_PERCENTAGE;
+
+                let adjustment = if good_qty > mean {
+                    let excess = (good_qty / mean - 1.0)
+                        / (MAX_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE - 1.0);
+                    1.0 - max_excess_discount * excess.min(1.0)
+                } else if mean > cushion {
+                    let shortage = (mean - good_qty) / (mean - cushion);
+                    1.0 + max_shortage_premium * shortage.clamp(0.0, 1.0)
+                } else {
+                    1.0
+                };
+                ctx.default_price * base * adjustment
+            }
+            SellSpread::Volatility {
+                floor,
+                per_trade,
+                max,
+            } => {
+                let spread = floor + per_trade * ctx.recent_activity as f32;
+                buy_exchange_rate * (1.0 - spread.min(max))
+            }
+            SellSpread::Custom(spread) => spread(buy_exchange_rate, ctx),
+        };
+        sell_exchange_rate.min(buy_exchange_rate * MAX_SELL_TO_BUY_RATIO)
+    }
+}
+
+/// Which `mean` the goods are priced against
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MeanMode {
+    /// The mean of the initial quantities, as documented
+    #[default]
+    Initial,
+    /// The mean of the current quantities, kept up to date as goods are locked, bought, sold and rebalanced
+    Live,
+}
+
+/// Where the prices of idle goods decay to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DecayTarget {
+    /// The default exchange rates
+    DefaultExchangeRate,
+    /// The last exchange rate seen in the events of the other markets, or the default one if none was seen
+    CompetitorPrices,
+}
+
+/// Decay of the prices of the goods that nobody trades: every day after `idle_days` days without trades,
+/// the buy exchange rate covers `rate` of its distance to the target
+#[derive(Debug, Clone, Copy)]
+pub struct IdleDecay {
+    pub idle_days: u64,
+    pub rate: f32,
+    pub target: DecayTarget,
+}
+
+/// Shape of the price deflation above the mean
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DeflationCurve {
+    /// The documented tiers, the price jumps at every threshold
+    #[default]
+    Step,
+    /// Linear interpolation between the tier thresholds, continuous and non increasing in quantity
+    Linear,
+    /// Exponential interpolation between the tier thresholds, continuous and non increasing in quantity
+    Exponential,
+}
+
+/// The strategy described in the crate docs: tiered inflation below the mean, tiered deflation above it,
+/// and tiered discounts on big buys.
+pub struct TieredPricing {
+    /// Shape of the deflation above the mean, `Step` follows the documented tiers
+    pub curve: DeflationCurve,
+    /// Volume tiers of `lock buy`, relative to the quantity held by the market
+    pub buy_volume_tiers: Vec<VolumeTier>,
+    /// Volume tiers of `lock sell`, relative to the quantity held by the market and applied
+    /// only to goods above the mean, none by default
+    pub sell_volume_tiers: Vec<VolumeTier>,
+    /// How the sell exchange rate is derived, `SellSpread::Fixed(BUY_TO_SELL_PERCENTAGE)` by default
+    pub sell_spread: SellSpread,
+}
+
+impl Default for TieredPricing {
+    fn default() -> Self {
+        TieredPricing {
+            curve: DeflationCurve::Step,
+            buy_volume_tiers: VolumeTier::default_buy_tiers(),
+            sell_volume_tiers: Vec::new(),
+            sell_spread: SellSpread::Fixed(BUY_TO_SELL_PERCENTAGE),
+        }
+    }
+}
+
+impl TieredPricing {
+    // * Discount of a smooth curve, `position` is the good eur value over the mean (at least 1.0)
+    fn smooth_deflation_discount(&self, position: f32) -> f32 {
+        let mut previous = DEFLATION_ANCHORS[0];
+        for anchor in DEFLATION_ANCHORS.iter().skip(1) {
+            if position < anchor.0 {
+                let progress = (position - previous.0) / (anchor.0 - previous.0);
+                return match self.curve {
+                    DeflationCurve::Exponential => {
+                        previous.1 * (anchor.1 / previous.1).powf(progress)
+                    }
+                    _ => previous.1 + (anchor.1 - previous.1) * progress,
+                };
+            }
+            previous = *anchor;
+        }
+        previous.1
+    }
+}
+
+impl PricingStrategy for TieredPricing {
+    fn buy_exchange_rate(&self, ctx: &PricingContext) -> f32 {
+        let (good_qty, initial_good_qty, mean) = (ctx.value(), ctx.initial_value(), ctx.mean);
+
+        if good_qty < mean * DEFAULT_PRICE_LOWER_BOUND_QTY_PERCENTAGE {
+            (((1.0
+                - (good_qty - initial_good_qty * MINIMUM_GOOD_QUANTITY_PERCENTAGE)
+                    / (mean - initial_good_qty * MINIMUM_GOOD_QUANTITY_PERCENTAGE))
+                * MAX_INFLATION_PRICE_INCREASE_PERCENTAGE)
+                + 1.0)
+                * ctx.default_price
+        } else if self.curve != DeflationCurve::Step {
+            ctx.default_price * self.smooth_deflation_discount(good_qty / mean)
+        } else if good_qty < mean * FIRST_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE {
+            ctx.default_price
+        } else if good_qty < mean * SECOND_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE {
+            ctx.default_price * FIRST_DEFLATION_PRICE_DISCOUNT
+        } else if good_qty < mean * THIRD_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE {
+            ctx.default_price * SECOND_DEFLATION_PRICE_DISCOUNT
+        } else if good_qty < mean * MAX_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE {
+            ctx.default_price * THIRD_DEFLATION_PRICE_DISCOUNT
+        } else {
+            ctx.default_price * MAX_DEFLATION_PRICE_DISCOUNT
+        }
+    }
+
+    fn sell_exchange_rate(&self, buy_exchange_rate: f32, ctx: &PricingContext) -> f32 {
+        self.sell_spread.sell_exchange_rate(buy_exchange_rate, ctx)
+    }
+
+    fn buy_volume_multiplier(&self, quantity: f32, ctx: &PricingContext) -> f32 {
+        volume_tier_multiplier(&self.buy_volume_tiers, quantity, ctx.quantity)
+    }
+
+    fn sell_volume_multiplier(&self, quantity: f32, ctx: &PricingContext) -> f32 {
+        // * Only the goods the market already has in excess are paid less
+        if ctx.value() <= ctx.mean {
+            return 1.0;
+        }
+        volume_tier_multiplier(&self.sell_volume_tiers, quantity, ctx.quantity)
+    }
+
+    fn price_tier(&self, ctx: &PricingContext) -> PriceTier {
+        let (good_qty, mean) = (ctx.value(), ctx.mean);
+
+        if good_qty < mean * DEFAULT_PRICE_LOWER_BOUND_QTY_PERCENTAGE {
+            PriceTier::Inflation {
+                premium: self.buy_exchange_rate(ctx) / ctx.default_price - 1.0,
+            }
+        } else if self.curve != DeflationCurve::Step {
+            PriceTier::Curve {
+                discount: self.smooth_deflation_discount(good_qty / mean),
+            }
+        } else if good_qty < mean * FIRST_DEFLATION_PRICE_LOWER_BOUND_QTY_PERCENTAGE {
+            PriceTier::Default
+        } else {
+            // * Highest deflation threshold reached by the good
+            let mut tier = 1;
+            for (index, anchor) in DEFLATION_ANCHORS.iter().enumerate().skip(2) {
+                if good_qty >= mean * anchor.0 {
+                    tier = index;
+                }
+            }
+            PriceTier::Deflation {
+                tier,
+                discount: DEFLATION_ANCHORS[tier].1,
+            }
+        }
+    }
+
+    fn buy_volume_tier(&self, quantity: f32, ctx: &PricingContext) -> Option<usize> {
+        volume_tier(&self.buy_volume_tiers, quantity, ctx.quantity)
+    }
+
+    fn sell_volume_tier(&self, quantity: f32, ctx: &PricingContext) -> Option<usize> {
+        if ctx.value() <= ctx.mean {
+            return None;
+        }
+        volume_tier(&self.sell_volume_tiers, quantity, ctx.quantity)
+    }
+}
+
+/// Logarithmic market maker curve: the price moves with the logarithm of how far the good is from the mean,
+/// `price = (1 + liquidity * ln(mean / value)) * defaultPrice`, bounded by `[min_price_ratio, max_price_ratio] * defaultPrice`.
+pub struct LogarithmicPricing {
+    /// How strongly the price reacts to the distance from the mean
+    pub liquidity: f32,
+    pub min_price_ratio: f32,
+    pub max_price_ratio: f32,
+}
+
+impl Default for LogarithmicPricing {
+    fn default() -> Self {
+        LogarithmicPricing {
+            liquidity: DEFAULT_LOGARITHMIC_LIQUIDITY,
+            min_price_ratio: DEFAULT_LOGARITHMIC_MIN_PRICE_RATIO,
+            max_price_ratio: DEFAULT_LOGARITHMIC_MAX_PRICE_RATIO,
+        }
+    }
+}
+
+impl PricingStrategy for LogarithmicPricing {
+    fn buy_exchange_rate(&self, ctx: &PricingContext) -> f32 {
+        let good_qty = ctx.value();
+
+        // * An empty good is priced at the highest allowed rate
+        if good_qty <= 0.0 {
+            return ctx.default_price * self.max_price_ratio;
+        }
+
+        let ratio = 1.0 + self.liquidity * (ctx.mean / good_qty).ln();
+        ratio.max(self.min_price_ratio).min(self.max_price_ratio) * ctx.default_price
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEAN: f32 = 1000.0;
+
+    fn ctx(quantity: f32) -> PricingContext {
+        PricingContext {
+            kind: GoodKind::USD,
+            default_price: 1.0,
+            quantity,
+            initialization_qty: MEAN,
+            mean: MEAN,
+            recent_activity: 0,
+        }
+    }
+
+    fn smooth_curves() -> Vec<TieredPricing> {
+        [DeflationCurve::Linear, DeflationCurve::Exponential]
+            .iter()
+            .map(|curve| TieredPricing {
+                curve: *curve,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn smooth_curves_never_increase_with_quantity() {
+        for pricing in smooth_curves() {
+            // * From the reserve cushion up to twice the mean, past the last anchor
+            let mut quantity = MEAN * MINIMUM_GOOD_QUANTITY_PERCENTAGE;
+            let mut previous = pricing.buy_exchange_rate(&ctx(quantity));
+            while quantity < 2.0 * MEAN {
+                quantity += 0.5;
+                let rate = pricing.buy_exchange_rate(&ctx(quantity));
+                assert!(
+                    rate <= previous + 1e-6,
+                    "{:?}: rate {} at quantity {} is above {}",
+                    pricing.curve,
+                    rate,
+                    quantity,
+                    previous
+                );
+                previous = rate;
+            }
+        }
+    }
+
+    #[test]
+    fn smooth_curves_hit_the_tier_anchors() {
+        for pricing in smooth_curves() {
+            for (position, discount) in DEFLATION_ANCHORS.iter() {
+                let rate = pricing.buy_exchange_rate(&ctx(MEAN * position));
+                assert!(
+                    (rate - discount).abs() < 1e-4,
+                    "{:?}: rate {} at position {} instead of {}",
+                    pricing.curve,
+                    rate,
+                    position,
+                    discount
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn smooth_curves_meet_the_inflation_branch_at_the_mean() {
+        for pricing in smooth_curves() {
+            let below = pricing.buy_exchange_rate(&ctx(MEAN - 0.01));
+            let at = pricing.buy_exchange_rate(&ctx(MEAN));
+            assert!(
+                (below - at).abs() < 1e-4,
+                "{:?}: rate jumps from {} to {} at the mean",
+                pricing.curve,
+                below,
+                at
+            );
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use unitn_market_2022::good::good_kind::GoodKind;
+
+use crate::{LockSide, PriceTier};
+
+/// A price the market guarantees until `valid_until`, returned by `quote_buy` and `quote_sell`
+/// and turned into a lock by `lock_buy_quote` and `lock_sell_quote`
+#[derive(Debug, Clone)]
+pub struct Quote {
+    pub id: String,
+    pub side: LockSide,
+    pub kind: GoodKind,
+    pub quantity: f32,
+    /// Eur for the whole quantity
+    pub price: f32,
+    /// Eur for one unit, volume discounts included
+    pub unit_rate: f32,
+    /// Volume tier applied to the order, counted from 1, `None` if no tier applies
+    pub volume_tier: Option<usize>,
+    /// Last market day in which the quote can be used
+    pub valid_until: u64,
+}
+
+/// Error returned when a quote is turned into a lock
+#[derive(Debug)]
+pub enum QuoteLockError<E> {
+    /// The quote has never been issued for that side, has already been used or has been dropped
+    UnknownQuote { quote_id: String },
+    /// The quote has not been used in time
+    ExpiredQuote { quote_id: String, valid_until: u64 },
+    /// Any error that locking at the quoted price would return
+    Market(E),
+}
+
+/// Step by step computation of a price, returned by `explain_buy` and `explain_sell`
+#[derive(Debug, Clone)]
+pub struct PriceBreakdown {
+    pub side: LockSide,
+    pub kind: GoodKind,
+    pub quantity: f32,
+    /// See `PricingContext::default_price`
+    pub default_rate: f32,
+    /// See `PricingContext::value`
+    pub value: f32,
+    /// See `PricingContext::mean`
+    pub mean: f32,
+    /// `value / mean`, below `1.0` the good is scarce, above it is in excess
+    pub position: f32,
+    /// Region of the price curve the good is in
+    pub price_tier: PriceTier,
+    /// Current exchange rate of the side (buy or sell)
+    pub exchange_rate: f32,
+    /// Price of the whole quantity before the volume tiers
+    pub base_price: f32,
+    /// Same as `Quote::volume_tier`
+    pub volume_tier: Option<usize>,
+    /// Multiplier of the volume tier, `1.0` if no tier applies
+    pub volume_multiplier: f32,
+    /// Eur for the whole quantity, as returned by `get_buy_price` or `get_sell_price`
+    pub price: f32,
+}
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+
+use rand::{thread_rng, Rng};
+use unitn_market_2022::good::good_kind::GoodKind;
+
+use crate::{KindOfTrade, PROBABILITY_OF_REBALANCE};
+
+//Eur reserve strategy default constants
+const DEFAULT_EUR_RESERVE_SHARE: f32 = 0.30;
+
+/// What the market knows about a good when rebalancing
+#[derive(Debug, Clone, Copy)]
+pub struct RebalanceGood {
+    pub kind: GoodKind,
+    /// Eur value of the quantity held (locked goods excluded), at the default exchange rate
+    pub value: f32,
+    /// See `PricingContext::initial_value`
+    pub initial_value: f32,
+    /// Eur value the good can not go below, because of the reserve floors of the market
+    pub floor_value: f32,
+    pub kind_of_trade: KindOfTrade,
+    /// Whether this is the base good of the market, eur by default, which is never marked
+    pub is_base: bool,
+}
+
+/// Conversion of `eur_value` eur worth of `from` into `to`, at the default exchange rates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transfer {
+    pub from: GoodKind,
+    pub to: GoodKind,
+    pub eur_value: f32,
+}
+
+/// Trade status of a good, returned by `trade_statuses`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradeStatus {
+    pub kind: GoodKind,
+    pub kind_of_trade: KindOfTrade,
+    /// Market day in which the good was last marked as imported or exported, `None` if it is not marked
+    pub set_day: Option<u64>,
+    /// Market day in which the mark will be reset, `None` if it is not marked
+    pub resets_on: Option<u64>,
+}
+
+/// A conversion applied by a rebalance, returned by `rebalance_history`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RebalanceRecord {
+    pub day: u64,
+    pub from: GoodKind,
+    pub to: GoodKind,
+    /// Eur value taken from `from`, at the default exchange rates
+    pub eur_value: f32,
+    /// Part of `eur_value` lost because of the rebalance fee
+    pub fee: f32,
+}
+
+/// Rebalancing policy of the market, asked for a plan every time a rebalance occurs
+pub trait RebalanceStrategy {
+    /// Conversions to apply, in order. The market marks `to` as imported and `from` as exported
+    /// (except the base good), and never converts what would take `from` below its `floor_value`.
+    fn plan(&self, goods: &[RebalanceGood]) -> Vec<Transfer>;
+}
+
+/// When the market rebalances its goods, checked on every time increment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RebalanceSchedule {
+    /// With the given probability, as documented
+    Random { probability: f32 },
+    /// Every `days` days
+    Every { days: u64 },
+    /// When the eur value of a good is further than `imbalance` times the mean from the mean of all the goods,
+    /// the base one included
+    Threshold { imbalance: f32 },
+}
+
+impl Default for RebalanceSchedule {
+    fn default() -> Self {
+        RebalanceSchedule::Random {
+            probability: PROBABILITY_OF_REBALANCE,
+        }
+    }
+}
+
+impl RebalanceSchedule {
+    // * Panics if the schedule would silently never rebalance
+    pub(crate) fn validate(&self) {
+        if let RebalanceSchedule::Every { days: 0 } = self {
+            panic!("A rebalance schedule must have a positive number of days, found 0 !");
+        }
+    }
+
+    pub(crate) fn is_due(&self, day: u64, goods: &[RebalanceGood]) -> bool {
+        match *self {
+            RebalanceSchedule::Random { probability } => {
+                thread_rng().gen_range(0.0, 1.0) < probability
+            }
+            RebalanceSchedule::Every { days } => day.is_multiple_of(days),
+            RebalanceSchedule::Threshold { imbalance } => {
+                let mean = goods.iter().map(|good| good.value).sum::<f32>() / goods.len() as f32;
+                goods
+                    .iter()
+                    .any(|good| (good.value - mean).abs() > imbalance * mean)
+            }
+        }
+    }
+}
+
+/// The strategy described in the crate docs: value moves from the richest good that is not imported
+/// to the poorest good that is not exported, until they meet the mean of all the goods (the base one included).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MeanBalancing;
+
+impl RebalanceStrategy for MeanBalancing {
+    fn plan(&self, goods: &[RebalanceGood]) -> Vec<Transfer> {
+        let mean = goods.iter().map(|good| good.value).sum::<f32>() / goods.len() as f32;
+        let targets = goods.iter().map(|good| (good.kind, mean)).collect();
+        greedy_plan(goods, &targets, None)
+    }
+}
+
+/// Never rebalances
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoRebalance;
+
+impl RebalanceStrategy for NoRebalance {
+    fn plan(&self, _goods: &[RebalanceGood]) -> Vec<Transfer> {
+        Vec::new()
+    }
+}
+
+/// Moves value towards a target portfolio, where every good holds `weight / sum of the weights`
+/// of the total value. Goods without a weight are left untouched.
+#[derive(Debug, Clone, Default)]
+pub struct TargetWeights {
+    pub weights: HashMap<GoodKind, f32>,
+}
+
+impl RebalanceStrategy for TargetWeights {
+    fn plan(&self, goods: &[RebalanceGood]) -> Vec<Transfer> {
+        let weighted: Vec<RebalanceGood> = goods
+            .iter()
+            .filter(|good| self.weights.contains_key(&good.kind))
+            .copied()
+            .collect();
+        let total_value: f32 = weighted.iter().map(|good| good.value).sum();
+        let total_weight: f32 = weighted.iter().map(|good| self.weights[&good.kind]).sum();
+        if total_weight <= 0.0 {
+            return Vec::new();
+        }
+
+        let targets = weighted
+            .iter()
+            .map(|good| {
+                let share = self.weights[&good.kind] / total_weight;
+                (good.kind, total_value * share)
+            })
+            .collect();
+        greedy_plan(&weighted, &targets, None)
+    }
+}
+
+/// Only keeps the reserve of the base good (eur by default) at `share` of the total value, converting from
+/// (or into) the other goods, which are otherwise left as they are.
+#[derive(Debug, Clone, Copy)]
+pub struct EurReserve {
+    pub share: f32,
+}
+
+impl Default for EurReserve {
+    fn default() -> Self {
+        EurReserve {
+            share: DEFAULT_EUR_RESERVE_SHARE,
+        }
+    }
+}
+
+impl RebalanceStrategy for EurReserve {
+    fn plan(&self, goods: &[RebalanceGood]) -> Vec<Transfer> {
+        let base = match goods.iter().find(|good| good.is_base) {
+            Some(good) => good.kind,
+            None => return Vec::new(),
+        };
+        let total_value: f32 = goods.iter().map(|good| good.value).sum();
+        let other_goods = goods.len() - 1;
+        if other_goods == 0 {
+            return Vec::new();
+        }
+
+        let other_target = total_value * (1.0 - self.share) / other_goods as f32;
+        let targets = goods
+            .iter()
+            .map(|good| {
+                if good.is_base {
+                    (good.kind, total_value * self.share)
+                } else {
+                    (good.kind, other_target)
+                }
+            })
+            .collect();
+        greedy_plan(goods, &targets, Some(base))
+    }
+}
+
+// * Repeatedly moves value from the good most over its target (and not imported) to the good most under
+// * its target (and not exported), marking them as the market will do. Every transfer closes at least one
+// * of the two gaps (or takes the eligible good down to its floor), so the plan is finite. With `pivot` set, every transfer involves that good.
+fn greedy_plan(
+    goods: &[RebalanceGood],
+    targets: &HashMap<GoodKind, f32>,
+    pivot: Option<GoodKind>,
+) -> Vec<Transfer> {
+    let mut goods: Vec<RebalanceGood> = goods.to_vec();
+    let mut transfers = Vec::new();
+
+    loop {
+        // * With a pivot, the side of every transfer it takes part in is fixed by its own gap
+        let pivot_gap = match pivot {
+            Some(pivot) => match goods.iter().find(|good| good.kind == pivot) {
+                Some(good) if targets[&good.kind] != good.value => {
+                    Some((pivot, targets[&good.kind] - good.value))
+                }
+                _ => break,
+            },
+            None => None,
+        };
+        let may_import = |kind: GoodKind| match pivot_gap {
+            Some((pivot, gap)) => (gap > 0.0) == (kind == pivot),
+            None => true,
+        };
+        let may_export = |kind: GoodKind| match pivot_gap {
+            Some((pivot, gap)) => (gap < 0.0) == (kind == pivot),
+            None => true,
+        };
+
+        let (mut suffering, mut eligible): (Option<usize>, Option<usize>) = (None, None);
+        for (index, good) in goods.iter().enumerate() {
+            let gap = targets[&good.kind] - good.value;
+            if gap > 0.0 && good.kind_of_trade != KindOfTrade::Exported && may_import(good.kind) {
+                suffering = match suffering {
+                    Some(other) if gap <= targets[&goods[other].kind] - goods[other].value => {
+                        suffering
+                    }
+                    _ => Some(index),
+                };
+            } else if gap < 0.0
+                && good.value > good.floor_value
+                && good.kind_of_trade != KindOfTrade::Imported
+                && may_export(good.kind)
+            {
+                eligible = match eligible {
+                    Some(other) if -gap <= goods[other].value - targets[&goods[other].kind] => {
+                        eligible
+                    }
+                    _ => Some(index),
+                };
+            }
+        }
+
+        let (suffering, eligible) = match suffering.zip(eligible) {
+            Some(pair) => pair,
+            None => break,
+        };
+
+        let deficit = targets[&goods[suffering].kind] - goods[suffering].value;
+        // * The eligible good gives at most what it holds over its target and over its floor
+        let eligible_bound = targets[&goods[eligible].kind].max(goods[eligible].floor_value);
+        let surplus = goods[eligible].value - eligible_bound;
+        let eur_value = f32::min(deficit, surplus);
+
+        transfers.push(Transfer {
+            from: goods[eligible].kind,
+            to: goods[suffering].kind,
+            eur_value,
+        });
+
+        if !goods[suffering].is_base {
+            goods[suffering].kind_of_trade = KindOfTrade::Imported;
+        }
+        if !goods[eligible].is_base {
+            goods[eligible].kind_of_trade = KindOfTrade::Exported;
+        }
+        // * Snap the closed gap to its target, so that rounding errors can not keep the loop going
+        if deficit <= surplus {
+            goods[suffering].value = targets[&goods[suffering].kind];
+            goods[eligible].value -= eur_value;
+        } else {
+            goods[eligible].value = eligible_bound;
+            goods[suffering].value += eur_value;
+        }
+    }
+
+    transfers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn good(kind: GoodKind, value: f32) -> RebalanceGood {
+        RebalanceGood {
+            kind,
+            value,
+            initial_value: value,
+            floor_value: 0.0,
+            kind_of_trade: KindOfTrade::Unknown,
+            is_base: kind == GoodKind::EUR,
+        }
+    }
+
+    #[test]
+    fn every_schedule_is_due_on_multiples_of_its_days() {
+        let schedule = RebalanceSchedule::Every { days: 3 };
+        let due: Vec<u64> = (0..10).filter(|day| schedule.is_due(*day, &[])).collect();
+        assert_eq!(due, vec![0, 3, 6, 9]);
+    }
+
+    #[test]
+    fn threshold_schedule_is_due_past_the_imbalance() {
+        let schedule = RebalanceSchedule::Threshold { imbalance: 0.5 };
+        // * Mean of 1000, the furthest good is 400 away from it
+        let balanced = [
+            good(GoodKind::EUR, 1400.0),
+            good(GoodKind::USD, 800.0),
+            good(GoodKind::YEN, 800.0),
+        ];
+        assert!(!schedule.is_due(0, &balanced));
+
+        // * Mean of 1000, the furthest good is 600 away from it
+        let unbalanced = [
+            good(GoodKind::EUR, 1600.0),
+            good(GoodKind::USD, 700.0),
+            good(GoodKind::YEN, 700.0),
+        ];
+        assert!(schedule.is_due(0, &unbalanced));
+    }
+
+    #[test]
+    #[should_panic(expected = "positive number of days")]
+    fn every_zero_days_is_rejected() {
+        RebalanceSchedule::Every { days: 0 }.validate();
+    }
+}
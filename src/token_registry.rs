@@ -0,0 +1,154 @@
+use std::collections::{HashMap, VecDeque};
+
+/// Lifecycle state of a lock token
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenState {
+    /// The lock is still open, it may have been partially redeemed
+    Issued,
+    /// The lock has been fully redeemed through `buy` or `sell`
+    Redeemed,
+    /// The lock has been dropped after `MAX_LOCK_TIME` days
+    Expired,
+    /// The lock has been cancelled by its owner
+    Cancelled,
+    /// The token has never been issued, or it has already been pruned
+    Unknown,
+}
+
+/// State of a token together with the market day in which it was reached
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenRecord {
+    pub state: TokenState,
+    pub day: u64,
+}
+
+pub(crate) struct TokenRegistry {
+    records: HashMap<String, TokenRecord>,
+    retired: VecDeque<String>, // closed tokens, oldest first, used to prune the registry
+    retention_days: u64,
+    max_retired: usize,
+}
+
+impl TokenRegistry {
+    pub(crate) fn new(retention_days: u64, max_retired: usize) -> TokenRegistry {
+        TokenRegistry {
+            records: HashMap::new(),
+            retired: VecDeque::new(),
+            retention_days,
+            max_retired,
+        }
+    }
+
+    pub(crate) fn issue(&mut self, token: &str, day: u64) {
+        self.records.insert(
+            String::from(token),
+            TokenRecord {
+                state: TokenState::Issued,
+                day,
+            },
+        );
+    }
+
+    // * Closes the token with the given state and prunes the oldest closed tokens if needed
+    pub(crate) fn retire(&mut self, token: &str, state: TokenState, day: u64) {
+        self.records
+            .insert(String::from(token), TokenRecord { state, day });
+        self.retired.push_back(String::from(token));
+        self.prune(day);
+    }
+
+    pub(crate) fn record(&self, token: &str) -> Option<TokenRecord> {
+        self.records.get(token).copied()
+    }
+
+    pub(crate) fn state(&self, token: &str) -> TokenState {
+        match self.records.get(token) {
+            Some(record) => record.state,
+            None => TokenState::Unknown,
+        }
+    }
+
+    // * Forgets the closed tokens older than the retention window, and the oldest ones above the memory bound
+    pub(crate) fn prune(&mut self, day: u64) {
+        while let Some(token) = self.retired.front() {
+            let too_old = match self.records.get(token) {
+                Some(record) => record.day.saturating_add(self.retention_days) < day,
+                None => true,
+            };
+            if !too_old && self.retired.len() <= self.max_retired {
+                break;
+            }
+            let token = self.retired.pop_front().unwrap();
+            self.records.remove(&token);
+        }
+    }
+
+    // * Called when the market time is shifted back to avoid its overflow
+    pub(crate) fn shift(&mut self, offset: u64) {
+        for record in self.records.values_mut() {
+            record.day = record.day.saturating_sub(offset);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closed_tokens_are_pruned_after_the_retention_window() {
+        let mut registry = TokenRegistry::new(5, 100);
+        registry.issue("token", 0);
+        registry.retire("token", TokenState::Redeemed, 10);
+
+        registry.prune(15);
+        assert_eq!(registry.state("token"), TokenState::Redeemed);
+        registry.prune(16);
+        assert_eq!(registry.state("token"), TokenState::Unknown);
+    }
+
+    #[test]
+    fn oldest_closed_tokens_are_evicted_above_the_cap() {
+        let mut registry = TokenRegistry::new(100, 2);
+        for (day, token) in ["first", "second", "third"].iter().enumerate() {
+            registry.issue(token, day as u64);
+            registry.retire(token, TokenState::Redeemed, day as u64);
+        }
+
+        assert_eq!(registry.state("first"), TokenState::Unknown);
+        assert_eq!(registry.state("second"), TokenState::Redeemed);
+        assert_eq!(registry.state("third"), TokenState::Redeemed);
+    }
+
+    #[test]
+    fn retired_tokens_keep_their_terminal_state() {
+        let mut registry = TokenRegistry::new(100, 100);
+        let terminal = [
+            ("redeemed", TokenState::Redeemed),
+            ("expired", TokenState::Expired),
+            ("cancelled", TokenState::Cancelled),
+        ];
+        for (token, state) in terminal {
+            registry.issue(token, 1);
+            assert_eq!(registry.state(token), TokenState::Issued);
+            registry.retire(token, state, 2);
+            assert_eq!(registry.state(token), state);
+            assert_eq!(registry.record(token), Some(TokenRecord { state, day: 2 }));
+        }
+        assert_eq!(registry.state("never issued"), TokenState::Unknown);
+    }
+
+    #[test]
+    fn shift_moves_the_retention_window() {
+        let mut registry = TokenRegistry::new(5, 100);
+        registry.issue("token", 0);
+        registry.retire("token", TokenState::Expired, 10);
+
+        registry.shift(8);
+        assert_eq!(registry.record("token").unwrap().day, 2);
+        registry.prune(7);
+        assert_eq!(registry.state("token"), TokenState::Expired);
+        registry.prune(8);
+        assert_eq!(registry.state("token"), TokenState::Unknown);
+    }
+}